@@ -1,16 +1,103 @@
 use component::*;
 use entity::*;
+use type_info::{named_type_id, type_name};
 use std::any::{Any, TypeId};
+use std::fmt;
 
 /// data for systems, storing which components they should be intrested in
+#[derive(Clone)]
 pub struct Aspect {
     pub accept_types     : Vec<TypeId>,
-    pub not_accept_types : Vec<TypeId>
+    pub not_accept_types : Vec<TypeId>,
+    /// Entity should have at least one type of each group, see `any_of2`.
+    any_of_types         : Vec<Vec<TypeId>>
 }
 impl Aspect {
+    /// Aspect of entities with all accepted types and none of not accepted ones.
+    pub fn new(accept_types : Vec<TypeId>, not_accept_types : Vec<TypeId>) -> Aspect {
+        Aspect {
+            accept_types,
+            not_accept_types,
+            any_of_types : Vec::new()
+        }
+    }
+
+    /// Groups, added with `any_of2` and `any_of3`.
+    pub fn any_of_types(&self) -> &[Vec<TypeId>] {
+        &self.any_of_types
+    }
+
     pub fn check(&self, entity : &Entity) -> bool {
-        self.accept_types.iter().all(|ty| { entity.components.borrow().keys().any(|t| t == ty )}) &&
-            self.not_accept_types.iter().any(|ty| { entity.components.borrow().keys().any(|t| t == ty) }) == false
+        let components = entity.components.borrow();
+        self.accept_types.iter().all(|ty| components.contains_key(ty)) &&
+            !self.not_accept_types.iter().any(|ty| components.contains_key(ty)) &&
+            self.any_of_types.iter().all(|group| group.iter().any(|ty| components.contains_key(ty)))
+    }
+
+    /// Why entity does or does not satisfy this aspect.
+    /// Types are shown by their short type names, `World::why_not` shows their registered names.
+    pub fn explain(&self, entity : &Entity) -> AspectReport {
+        self.explain_with(entity, &type_name)
+    }
+
+    pub(crate) fn explain_with(&self, entity : &Entity, name : &dyn Fn(TypeId) -> String) -> AspectReport {
+        let components = entity.components.borrow();
+        let names = |types : &mut dyn Iterator<Item = &TypeId>| types.map(|ty| name(*ty)).collect::<Vec<_>>();
+        AspectReport {
+            missing      : names(&mut self.accept_types.iter().filter(|ty| !components.contains_key(ty))),
+            excluded     : names(&mut self.not_accept_types.iter().filter(|ty| components.contains_key(ty))),
+            unmet_any_of : self.any_of_types.iter()
+                .filter(|group| !group.iter().any(|ty| components.contains_key(ty)))
+                .map(|group| names(&mut group.iter()))
+                .collect()
+        }
+    }
+}
+
+impl fmt::Debug for Aspect {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let names = |types : &Vec<TypeId>| types.iter().map(|ty| type_name(*ty)).collect::<Vec<_>>();
+        f.debug_struct("Aspect")
+            .field("all", &names(&self.accept_types))
+            .field("except", &names(&self.not_accept_types))
+            .field("any_of", &self.any_of_types.iter().map(names).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Result of `Aspect::explain`, with type names.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AspectReport {
+    /// Required types, entity does not have.
+    pub missing      : Vec<String>,
+    /// Excluded types, entity has.
+    pub excluded     : Vec<String>,
+    /// Any-of groups, entity has no type from.
+    pub unmet_any_of : Vec<Vec<String>>
+}
+
+impl AspectReport {
+    pub fn is_match(&self) -> bool {
+        self.missing.is_empty() && self.excluded.is_empty() && self.unmet_any_of.is_empty()
+    }
+}
+
+impl fmt::Display for AspectReport {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        if self.is_match() {
+            return write!(f, "matches");
+        }
+        let mut reasons = vec![];
+        if !self.missing.is_empty() {
+            reasons.push(format!("missing: {}", self.missing.join(", ")));
+        }
+        if !self.excluded.is_empty() {
+            reasons.push(format!("excluded: {}", self.excluded.join(", ")));
+        }
+        for group in self.unmet_any_of.iter() {
+            reasons.push(format!("none of: {}", group.join(" | ")));
+        }
+        write!(f, "{}", reasons.join("; "))
     }
 }
 
@@ -18,41 +105,25 @@ impl Aspect {
 #[macro_export]
 macro_rules! aspect_all{( $ ($aspect:ty), * ) => {
     {
-        use std::any::TypeId;
-        Aspect {
-            accept_types : vec![$( TypeId::of::<$aspect>() ),*],
-            not_accept_types : Vec::new()
-        }
+        $crate::Aspect::new(vec![$( $crate::named_type_id::<$aspect>() ),*], Vec::new())
     }
 }}
 
 impl Aspect {
     pub fn all<T : Any + Component>() -> Aspect {
-        Aspect {
-            accept_types : vec![TypeId::of::<T>()],
-            not_accept_types : Vec::new()
-        }
+        Aspect::new(vec![named_type_id::<T>()], Vec::new())
     }
     pub fn all2<T : Any + Component, T1 : Any + Component>() -> Aspect {
-        Aspect {
-            accept_types : vec![TypeId::of::<T>(), TypeId::of::<T1>()],
-            not_accept_types : Vec::new()
-        }
+        Aspect::new(vec![named_type_id::<T>(), named_type_id::<T1>()], Vec::new())
     }
     pub fn all3<T : Any + Component, T1 : Any + Component, T2 : Any + Component>() -> Aspect {
-        Aspect {
-            accept_types : vec![TypeId::of::<T>(), TypeId::of::<T1>(), TypeId::of::<T2>()],
-            not_accept_types : Vec::new()
-        }
+        Aspect::new(vec![named_type_id::<T>(), named_type_id::<T1>(), named_type_id::<T2>()], Vec::new())
     }
     pub fn all4<T : Any + Component,
                 T1 : Any + Component,
                 T2 : Any + Component,
                 T3 : Any + Component>() -> Aspect {
-        Aspect {
-            accept_types : vec![TypeId::of::<T>(), TypeId::of::<T1>(), TypeId::of::<T2>(), TypeId::of::<T3>()],
-            not_accept_types : Vec::new()
-        }
+        Aspect::new(vec![named_type_id::<T>(), named_type_id::<T1>(), named_type_id::<T2>(), named_type_id::<T3>()], Vec::new())
     }
 
     pub fn all5<T : Any + Component,
@@ -60,34 +131,33 @@ impl Aspect {
                 T2 : Any + Component,
                 T3 : Any + Component,
                 T4 : Any + Component>() -> Aspect {
-        Aspect {
-            accept_types : vec![TypeId::of::<T>(), TypeId::of::<T1>(), TypeId::of::<T2>(), TypeId::of::<T3>(), TypeId::of::<T4>()],
-            not_accept_types : Vec::new()
-        }
+        Aspect::new(vec![named_type_id::<T>(), named_type_id::<T1>(), named_type_id::<T2>(), named_type_id::<T3>(), named_type_id::<T4>()], Vec::new())
     }
 
     pub fn except<T : Any + Component>(mut self) -> Aspect {
-        self.not_accept_types.push(TypeId::of::<T>());
-        Aspect {
-            accept_types : self.accept_types,
-            not_accept_types : self.not_accept_types
-        }
+        self.not_accept_types.push(named_type_id::<T>());
+        self
     }
     pub fn except2<T : Any + Component, T1 : Any + Component>(mut self) -> Aspect {
-        self.not_accept_types.push(TypeId::of::<T>());
-        self.not_accept_types.push(TypeId::of::<T1>());
-        Aspect {
-            accept_types : self.accept_types,
-            not_accept_types : self.not_accept_types
-        }
+        self.not_accept_types.push(named_type_id::<T>());
+        self.not_accept_types.push(named_type_id::<T1>());
+        self
     }
     pub fn except3<T : Any + Component, T1 : Any + Component, T2 : Any + Component>(mut self) -> Aspect {
-        self.not_accept_types.push(TypeId::of::<T>());
-        self.not_accept_types.push(TypeId::of::<T1>());
-        self.not_accept_types.push(TypeId::of::<T2>());
-        Aspect {
-            accept_types : self.accept_types,
-            not_accept_types : self.not_accept_types
-        }
+        self.not_accept_types.push(named_type_id::<T>());
+        self.not_accept_types.push(named_type_id::<T1>());
+        self.not_accept_types.push(named_type_id::<T2>());
+        self
+    }
+
+    /// Entity should have T or T1.
+    pub fn any_of2<T : Any + Component, T1 : Any + Component>(mut self) -> Aspect {
+        self.any_of_types.push(vec![named_type_id::<T>(), named_type_id::<T1>()]);
+        self
+    }
+    /// Entity should have T, T1 or T2.
+    pub fn any_of3<T : Any + Component, T1 : Any + Component, T2 : Any + Component>(mut self) -> Aspect {
+        self.any_of_types.push(vec![named_type_id::<T>(), named_type_id::<T1>(), named_type_id::<T2>()]);
+        self
     }
 }
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::any::{Any, TypeId};
+use std::hash::{Hash, Hasher};
+
+use world::World;
+
+/// FNV-1a hasher. Unlike `DefaultHasher` its output is fixed, so checksums
+/// may be compared between different builds and machines with the same endianness.
+pub struct StableHasher {
+    state : u64
+}
+
+impl Default for StableHasher {
+    fn default() -> StableHasher {
+        StableHasher::new()
+    }
+}
+
+impl StableHasher {
+    pub fn new() -> StableHasher {
+        StableHasher { state : 0xcbf2_9ce4_8422_2325 }
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes : &[u8]) {
+        for byte in bytes {
+            self.state ^= u64::from(*byte);
+            self.state = self.state.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl World {
+    /// Register component or resource type for `checksum`.
+    ///
+    /// Floats are not `Hash`, for them implement `Hash` manually, with `f32::to_bits`.
+    pub fn register_hash<T : Any + Hash>(&mut self) {
+        self.registry.register_hash::<T>();
+    }
+
+    /// Hash of all registered components and resources, to detect desync between
+    /// two simulations, running in lockstep.
+    /// Unregistered components do not affect the checksum.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+
+        let hashed = |hasher : &mut StableHasher, components : &HashMap<TypeId, Box<dyn Any>>| {
+            let mut registered = components.iter().filter_map(|(type_id, component)| {
+                self.registry.get(*type_id).and_then(|registration| {
+                    registration.hash.map(|hash| (&registration.name, hash, component))
+                })
+            }).collect::<Vec<_>>();
+            registered.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (name, hash, component) in registered {
+                hasher.write(name.as_bytes());
+                hash(&**component, hasher);
+            }
+        };
+
+        for (id, entity) in self.entities.iter() {
+            hasher.write_i32(id as i32);
+            hashed(&mut hasher, &entity.components.borrow());
+        }
+        hashed(&mut hasher, &self.resources.resources.borrow());
+
+        hasher.finish()
+    }
+}
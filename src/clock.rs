@@ -0,0 +1,151 @@
+use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use time::PreciseTime;
+
+/// Source of frame deltas for `World::update` and `World::run_fixed`.
+pub trait Clock {
+    /// Seconds passed since the previous call.
+    fn tick(&mut self) -> f32;
+}
+
+/// Wall clock.
+pub struct RealClock {
+    last : PreciseTime
+}
+
+impl Default for RealClock {
+    fn default() -> RealClock {
+        RealClock::new()
+    }
+}
+
+impl RealClock {
+    pub fn new() -> RealClock {
+        RealClock { last : PreciseTime::now() }
+    }
+}
+
+impl Clock for RealClock {
+    fn tick(&mut self) -> f32 {
+        let now = PreciseTime::now();
+        let delta = self.last.to(now);
+        self.last = now;
+        delta.num_microseconds().unwrap_or(0) as f32 / 1_000_000.0
+    }
+}
+
+/// Clock, advanced only by hand. Clones share the same time, so one clone may be given
+/// to the world and another one kept for advancing.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    pending : Rc<Cell<f32>>
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    /// Next tick will report this much more time.
+    pub fn advance(&self, seconds : f32) {
+        self.pending.set(self.pending.get() + seconds);
+    }
+}
+
+impl Clock for ManualClock {
+    fn tick(&mut self) -> f32 {
+        self.pending.replace(0.0)
+    }
+}
+
+/// Records deltas of another clock, or plays back recorded deltas, for replays.
+#[derive(Clone)]
+pub struct RecordedClock {
+    source   : Option<Rc<RefCell<Box<dyn Clock>>>>,
+    deltas   : Rc<RefCell<Vec<f32>>>,
+    position : usize
+}
+
+impl RecordedClock {
+    /// Pass ticks of the given clock through, remembering them.
+    pub fn record<C : Clock + 'static>(clock : C) -> RecordedClock {
+        RecordedClock {
+            source   : Some(Rc::new(RefCell::new(Box::new(clock)))),
+            deltas   : Rc::new(RefCell::new(vec![])),
+            position : 0
+        }
+    }
+
+    /// Report given deltas one by one, and zero after they are over.
+    pub fn playback(deltas : Vec<f32>) -> RecordedClock {
+        RecordedClock {
+            source   : None,
+            deltas   : Rc::new(RefCell::new(deltas)),
+            position : 0
+        }
+    }
+
+    /// All deltas, recorded or to be played back.
+    pub fn deltas(&self) -> Vec<f32> {
+        self.deltas.borrow().clone()
+    }
+}
+
+impl Clock for RecordedClock {
+    fn tick(&mut self) -> f32 {
+        match self.source {
+            Some(ref source) => {
+                let delta = source.borrow_mut().tick();
+                self.deltas.borrow_mut().push(delta);
+                delta
+            },
+            None => {
+                let delta = self.deltas.borrow().get(self.position).cloned().unwrap_or(0.0);
+                self.position += 1;
+                delta
+            }
+        }
+    }
+}
+
+/// Frame timing, always present in world resources, registered as "tinyecs::Time".
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Time {
+    /// Real delta of the last frame.
+    pub raw_delta   : f32,
+    /// Last frame delta, multiplied by `scale`.
+    pub delta       : f32,
+    /// Sum of all scaled deltas.
+    pub elapsed     : f64,
+    /// Sum of all real deltas.
+    pub raw_elapsed : f64,
+    /// Number of updated frames.
+    pub frame_count : u64,
+    /// 1 is normal speed, 0 is pause, 0.5 is slow motion.
+    /// Systems with `System::ignores_time_scale` always get real delta.
+    pub scale       : f32
+}
+
+impl Default for Time {
+    fn default() -> Time {
+        Time {
+            raw_delta   : 0.0,
+            delta       : 0.0,
+            elapsed     : 0.0,
+            raw_elapsed : 0.0,
+            frame_count : 0,
+            scale       : 1.0
+        }
+    }
+}
+
+impl Time {
+    pub(crate) fn advance(&mut self, raw_delta : f32) {
+        self.raw_delta = raw_delta;
+        self.delta = raw_delta * self.scale;
+        self.elapsed += self.delta as f64;
+        self.raw_elapsed += raw_delta as f64;
+        self.frame_count += 1;
+    }
+}
@@ -1,4 +1,31 @@
 use std::any::Any;
+use std::sync::{Arc, RwLock};
+
+use registry::ComponentRegistry;
+
+/// How entity keeps the component.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Storage {
+    /// In a box, owned by entity.
+    Boxed,
+    /// Behind `Arc<RwLock<T>>`, like components added with `Entity::add_sync_component`.
+    Sync
+}
+
+/// Box component as storage says, see `Entity::add_component`.
+pub(crate) fn box_component<T : Any>(component : T, storage : Storage) -> Box<dyn Any> {
+    match storage {
+        Storage::Boxed => Box::new(component),
+        Storage::Sync => Box::new(Arc::new(RwLock::new(component)))
+    }
+}
 
 pub trait Component : Any {
+    /// Chosen with `#[component(storage = "...")]` of the derive, used by `Entity::add_component`.
+    const STORAGE : Storage = Storage::Boxed;
+
+    /// Add this type to the registry, called by `World::register_component`.
+    /// Generated by `#[derive(Component)]` from its attributes.
+    fn register(_ : &mut ComponentRegistry) where Self : Sized {
+    }
 }
@@ -0,0 +1,269 @@
+//! Debug console: text commands against the world, for any frontend - stdin or in-game UI.
+//!
+//! ```ignore
+//! let mut console = Console::new();
+//! println!("{}", console.execute(&mut world, "spawn Position Health").unwrap());
+//! console.execute(&mut world, "set 1 Health.hp 50").unwrap();
+//! ```
+//!
+//! Components are found by registered or type name. `spawn` needs `World::register_default`,
+//! `set` and `get` need `World::register_reflect`.
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::error::Error;
+
+use world::{World, SystemHandle};
+use reflect::ReflectValue;
+use type_info::type_name;
+
+/// Word of input, quoted words are kept as strings by `set`.
+struct Arg {
+    text   : String,
+    quoted : bool
+}
+
+impl Arg {
+    fn new() -> Arg {
+        Arg {
+            text   : String::new(),
+            quoted : false
+        }
+    }
+}
+
+/// Command implementation, gets the world and arguments after the command name.
+type CommandFn = Box<dyn FnMut(&mut World, &[Arg]) -> Result<String, String>>;
+
+struct Command {
+    help : String,
+    f    : CommandFn
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsoleError {
+    Empty,
+    UnknownCommand(String),
+    /// Unclosed quote in the input.
+    Parse(String),
+    Command { name : String, message : String }
+}
+
+impl fmt::Display for ConsoleError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConsoleError::Empty => write!(f, "empty command"),
+            ConsoleError::UnknownCommand(ref name) => write!(f, "unknown command \"{}\", try help", name),
+            ConsoleError::Parse(ref message) => write!(f, "{}", message),
+            ConsoleError::Command { ref name, ref message } => write!(f, "{}: {}", name, message)
+        }
+    }
+}
+
+impl Error for ConsoleError {}
+
+/// Registry of named commands.
+pub struct Console {
+    commands : BTreeMap<String, Command>
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        Console::new()
+    }
+}
+
+impl Console {
+    /// Console with built-in commands, see `help`.
+    pub fn new() -> Console {
+        let mut console = Console::empty();
+        console.register("spawn", "spawn [Component..] - create entity with default components", spawn);
+        console.register("destroy", "destroy <id> - remove entity", destroy);
+        console.register("list", "list [Component..] - entities with all given components", list);
+        console.register("inspect", "inspect <id> - components and systems of entity", inspect);
+        console.register("get", "get <id> <Component.field> - read field", get);
+        console.insert("set", "set <id> <Component.field> <value> - change field, quoted value is a string", Box::new(set));
+        console.register("systems", "systems - all systems with their handles", systems);
+        console.register("enable", "enable <system> - enable system by handle or name", |world, args| toggle(world, args, true));
+        console.register("disable", "disable <system> - disable system by handle or name", |world, args| toggle(world, args, false));
+        console
+    }
+
+    /// Console without commands.
+    pub fn empty() -> Console {
+        Console {
+            commands : BTreeMap::new()
+        }
+    }
+
+    /// Add or replace a command. Help line is shown by `help` command.
+    /// `help` itself is reserved and can not be registered.
+    pub fn register<F>(&mut self, name : &str, help : &str, mut f : F)
+        where F : FnMut(&mut World, &[String]) -> Result<String, String> + 'static {
+        self.insert(name, help, Box::new(move |world, args| {
+            f(world, &args.iter().map(|arg| arg.text.clone()).collect::<Vec<_>>())
+        }));
+    }
+
+    fn insert(&mut self, name : &str, help : &str, f : CommandFn) {
+        assert!(name != "help", "Command help is reserved");
+        self.commands.insert(name.to_string(), Command {
+            help : help.to_string(),
+            f
+        });
+    }
+
+    /// Names and help lines of all commands, sorted by name.
+    pub fn commands(&self) -> Vec<(&str, &str)> {
+        self.commands.iter().map(|(name, command)| (&name[..], &command.help[..])).collect()
+    }
+
+    /// Run one line of input, returns command output.
+    pub fn execute(&mut self, world : &mut World, line : &str) -> Result<String, ConsoleError> {
+        let mut args = tokenize(line)?;
+        if args.is_empty() {
+            return Err(ConsoleError::Empty);
+        }
+        let name = args.remove(0).text;
+        if name == "help" {
+            return Ok(self.commands().iter().map(|&(_, help)| help).collect::<Vec<_>>().join("\n"));
+        }
+        let command = match self.commands.get_mut(&name) {
+            Some(command) => command,
+            None => return Err(ConsoleError::UnknownCommand(name))
+        };
+        (command.f)(world, &args).map_err(|message| ConsoleError::Command { name, message })
+    }
+}
+
+/// Split by whitespace, double quotes group words.
+fn tokenize(line : &str) -> Result<Vec<Arg>, ConsoleError> {
+    let mut args = vec![];
+    let mut current = None::<Arg>;
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.get_or_insert_with(Arg::new).quoted = true;
+            },
+            c if c.is_whitespace() && !quoted => args.extend(current.take()),
+            c => current.get_or_insert_with(Arg::new).text.push(c)
+        }
+    }
+    if quoted {
+        return Err(ConsoleError::Parse("unclosed quote".to_string()));
+    }
+    args.extend(current.take());
+    Ok(args)
+}
+
+fn find_type(world : &World, name : &str) -> Result<TypeId, String> {
+    world.registry().find(name).map(|registration| registration.type_id)
+        .ok_or_else(|| format!("unknown component \"{}\"", name))
+}
+
+fn parse_id(arg : Option<&str>) -> Result<i32, String> {
+    let arg = arg.ok_or_else(|| "entity id expected".to_string())?;
+    arg.parse().map_err(|_| format!("\"{}\" is not an entity id", arg))
+}
+
+fn spawn(world : &mut World, args : &[String]) -> Result<String, String> {
+    let mut components = vec![];
+    for name in args {
+        let type_id = find_type(world, name)?;
+        let default = world.registry().get(type_id).and_then(|registration| registration.default)
+            .ok_or_else(|| format!("\"{}\" is not registered with register_default", name))?;
+        let storage = world.registry().get(type_id).unwrap().storage;
+        components.push((type_id, default(storage)));
+    }
+    let mut entity_manager = world.entity_manager();
+    let entity = entity_manager.create_entity();
+    for (type_id, component) in components {
+        entity.add_boxed_component(type_id, component);
+    }
+    Ok(format!("spawned {}", entity.id))
+}
+
+fn destroy(world : &mut World, args : &[String]) -> Result<String, String> {
+    let id = parse_id(args.first().map(|arg| &arg[..]))?;
+    if world.remove_entity(id) {
+        Ok(format!("destroyed {}", id))
+    } else {
+        Err(format!("no entity {}", id))
+    }
+}
+
+fn list(world : &mut World, args : &[String]) -> Result<String, String> {
+    let types = args.iter().map(|name| find_type(world, name)).collect::<Result<Vec<_>, _>>()?;
+    let lines = world.entities.values()
+        .filter(|entity| {
+            let components = entity.components.borrow();
+            types.iter().all(|type_id| components.contains_key(type_id))
+        })
+        .map(|entity| {
+            let mut names = entity.components.borrow().keys().map(|type_id| type_name(*type_id)).collect::<Vec<_>>();
+            names.sort();
+            format!("{}: {}", entity.id, names.join(", "))
+        })
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+fn inspect(world : &mut World, args : &[String]) -> Result<String, String> {
+    let id = parse_id(args.first().map(|arg| &arg[..]))?;
+    match world.inspect_entity(id) {
+        Some(report) => Ok(report.to_string().trim_end().to_string()),
+        None => Err(format!("no entity {}", id))
+    }
+}
+
+fn get(world : &mut World, args : &[String]) -> Result<String, String> {
+    let id = parse_id(args.first().map(|arg| &arg[..]))?;
+    let path = args.get(1).ok_or_else(|| "field path expected".to_string())?;
+    let entity = world.entities.get(id as usize).ok_or_else(|| format!("no entity {}", id))?;
+    entity.get_path(&world.registry, path).map(|value| value.to_string()).map_err(|error| error.to_string())
+}
+
+fn set(world : &mut World, args : &[Arg]) -> Result<String, String> {
+    let id = parse_id(args.first().map(|arg| &arg.text[..]))?;
+    let (path, value) = match (args.get(1), args.get(2)) {
+        (Some(path), Some(value)) => (&path.text, value),
+        _ => return Err("field path and value expected".to_string())
+    };
+    let value = if value.quoted { ReflectValue::String(value.text.clone()) } else { ReflectValue::parse(&value.text) };
+    let entity = world.entities.get(id as usize).ok_or_else(|| format!("no entity {}", id))?;
+    entity.set_path(&world.registry, path, value).map_err(|error| error.to_string())?;
+    entity.get_path(&world.registry, path).map(|value| format!("{} = {}", path, value)).map_err(|error| error.to_string())
+}
+
+fn systems(world : &mut World, _ : &[String]) -> Result<String, String> {
+    let lines = world.systems.iter().map(|(data, selected)| {
+        format!("{}: {} [{}] {}, {} entities", data.handle.0, type_name_of_system(&data.system.get_name()),
+                data.group.0, if data.enabled { "enabled" } else { "disabled" }, selected.entity_set.len())
+    }).collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+/// System names are full type names, show them without module path.
+fn type_name_of_system(name : &str) -> &str {
+    match name.find('<') {
+        Some(generics) => name[.. generics].rsplit("::").next().unwrap_or(name),
+        None => name.rsplit("::").next().unwrap_or(name)
+    }
+}
+
+fn find_system(world : &World, arg : &str) -> Result<SystemHandle, String> {
+    let found = world.systems.iter().map(|s| &s.0).find(|data| {
+        arg.parse::<u32>().ok() == Some(data.handle.0) || type_name_of_system(&data.system.get_name()) == arg
+    });
+    found.map(|data| data.handle).ok_or_else(|| format!("no system \"{}\"", arg))
+}
+
+fn toggle(world : &mut World, args : &[String], enabled : bool) -> Result<String, String> {
+    let arg = args.first().ok_or_else(|| "system handle or name expected".to_string())?;
+    let handle = find_system(world, arg)?;
+    world.set_enabled(handle, enabled);
+    Ok(format!("{} {}", if enabled { "enabled" } else { "disabled" }, arg))
+}
@@ -2,19 +2,37 @@ use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut, Drop};
 use std::any::{Any, TypeId};
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::atomic::{AtomicU64, Ordering};
 use component::*;
+use type_info::{named_type_id, name_of};
+
+/// Source of change ticks, shared by all entities, so ticks of different entities never repeat.
+static CHANGE_TICK : AtomicU64 = AtomicU64::new(0);
+
+fn next_change_tick() -> u64 {
+    CHANGE_TICK.fetch_add(1, Ordering::Relaxed) + 1
+}
 
 pub struct Entity {
     pub id                       : i32,
     pub components               : RefCell<HashMap<TypeId, Box<Any>>>,
     pub removed_components       : RefCell<HashSet<TypeId>>,
-    fresh                        : RefCell<bool>
+    /// Components, taken out of `components` by guards at the moment.
+    pub(crate) borrowed          : RefCell<HashSet<TypeId>>,
+    fresh                        : RefCell<bool>,
+    change_tick                  : Cell<u64>,
+    /// Locks of sync components were handed out, they may be written at any time.
+    shared                       : Cell<bool>
 }
 
 pub struct ComponentGuard<'a, T : Any> {
     component  : Option<Box<T>>,
-    collection : &'a RefCell<HashMap<TypeId, Box<Any>>>
+    collection : &'a RefCell<HashMap<TypeId, Box<Any>>>,
+    borrowed   : &'a RefCell<HashSet<TypeId>>,
+    /// Change tick of the owning entity, None for resources.
+    changed    : Option<&'a Cell<u64>>
 }
 impl <'a, T : Any> Deref for ComponentGuard<'a, T> {
     type Target = T;
@@ -25,14 +43,62 @@ impl <'a, T : Any> Deref for ComponentGuard<'a, T> {
 
 impl <'a, T : Any> DerefMut for ComponentGuard<'a, T> {
     fn deref_mut(&mut self) -> &mut T {
+        if let Some(changed) = self.changed {
+            changed.set(next_change_tick());
+        }
         self.component.as_mut().unwrap()
     }
 }
+impl<'a, T : Any> ComponentGuard<'a, T> {
+    /// Guard of component, taken out of the collection, marked as borrowed until drop.
+    pub(crate) fn new(component : Box<T>, collection : &'a RefCell<HashMap<TypeId, Box<dyn Any>>>,
+                      borrowed : &'a RefCell<HashSet<TypeId>>) -> ComponentGuard<'a, T> {
+        borrowed.borrow_mut().insert(TypeId::of::<T>());
+        ComponentGuard {
+            component  : Some(component),
+            collection,
+            borrowed,
+            changed    : None
+        }
+    }
+}
 impl<'a, T : Any> Drop for ComponentGuard<'a, T> {
     fn drop(&mut self) {
         self.component.take().and_then(|component| {
             self.collection.borrow_mut().insert(TypeId::of::<T>(), component)
         });
+        self.borrowed.borrow_mut().remove(&TypeId::of::<T>());
+    }
+}
+
+/// Read access to sync component, keeps the component alive while locked.
+pub struct SyncReadGuard<T : 'static> {
+    // dropped before the lock it points to
+    guard : RwLockReadGuard<'static, T>,
+    _lock : Arc<RwLock<T>>
+}
+impl<T> Deref for SyncReadGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+/// Write access to sync component, keeps the component alive while locked.
+pub struct SyncWriteGuard<T : 'static> {
+    // dropped before the lock it points to
+    guard : RwLockWriteGuard<'static, T>,
+    _lock : Arc<RwLock<T>>
+}
+impl<T> Deref for SyncWriteGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+impl<T> DerefMut for SyncWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
     }
 }
 
@@ -42,10 +108,28 @@ impl Entity {
             id                      : id,
             components              : RefCell::new(HashMap::new()),
             removed_components      : RefCell::new(HashSet::new()),
-            fresh                   : RefCell::new(false)
+            borrowed                : RefCell::new(HashSet::new()),
+            fresh                   : RefCell::new(false),
+            change_tick             : Cell::new(next_change_tick()),
+            shared                  : Cell::new(false)
         }
     }
 
+    /// Tick of the last change of components, ticks only grow and are unique across entities.
+    /// Changes through `ComponentGuard`, adding and removing components are tracked,
+    /// entities with sync components, handed out with `sync_component`, are changed on every call.
+    pub fn change_tick(&self) -> u64 {
+        if self.shared.get() {
+            self.mark_changed();
+        }
+        self.change_tick.get()
+    }
+
+    /// Update change tick, needed after changing `components` directly.
+    pub fn mark_changed(&self) {
+        self.change_tick.set(next_change_tick());
+    }
+
     /// Mark this entity as not refreshed.
     /// On beginning of next frame new registered components will affect their systems.
     pub fn refresh(&self) {
@@ -59,16 +143,26 @@ impl Entity {
     pub fn is_fresh(&self) -> bool {
         *self.fresh.borrow() == true
     }
+    /// Add component, kept as `T::STORAGE` says.
+    /// `Storage::Sync` components are accessible only with *_sync_component(), like with `add_sync_component`.
     pub fn add_component<T : Any + Component>(&self, component : T) {
-        self.components.borrow_mut().insert(TypeId::of::<T>(), Box::new(component));
+        self.components.borrow_mut().insert(named_type_id::<T>(), box_component(component, T::STORAGE));
+        self.mark_changed();
+    }
+
+    /// Add already boxed component, used when the component type is known only at runtime.
+    pub(crate) fn add_boxed_component(&self, type_id : TypeId, component : Box<dyn Any>) {
+        self.components.borrow_mut().insert(type_id, component);
+        self.mark_changed();
     }
 
     /// Remove component of given type from entity
     /// Be carefull, if this component is borrowed at this moment, it will not be really deleted.
     pub fn remove_component<T : Any>(&self) {
         if self.removed_components.borrow_mut().insert(TypeId::of::<T>()) == false {
-            panic!("Removing of removed components");
+            panic!("Removing of removed component {} from entity {}", name_of::<T>(), self.id);
         }
+        self.mark_changed();
     }
 
     pub fn has_component<T : Any>(&self) -> bool {
@@ -78,12 +172,82 @@ impl Entity {
     /// Move component from entity to CompoentGuard. In general case, it behaves like &mut T.
     /// While component is borrowed, second get_component() with same type will cause panic
     pub fn get_component<T : Any + Component>(&self) -> ComponentGuard<T> {
-        let component = self.components.borrow_mut().remove(&TypeId::of::<T>()).unwrap();
-        let c : Box<T> = component.downcast().unwrap();
+        let component = match self.components.borrow_mut().remove(&TypeId::of::<T>()) {
+            Some(component) => component,
+            None => panic!("Entity {} has no component {} or it is already borrowed", self.id, name_of::<T>())
+        };
+        let c : Box<T> = match component.downcast() {
+            Ok(c) => c,
+            Err(component) => {
+                self.components.borrow_mut().insert(TypeId::of::<T>(), component);
+                panic!("Component {} was added with add_sync_component, use read_sync_component or write_sync_component",
+                       name_of::<T>())
+            }
+        };
 
-        ComponentGuard {
-            component: Some(c),
-            collection: &self.components,
+        let mut guard = ComponentGuard::new(c, &self.components, &self.borrowed);
+        guard.changed = Some(&self.change_tick);
+        guard
+    }
+
+    /// Add component behind `Arc<RwLock<T>>`, that can be shared with other threads.
+    /// Aspects see it as usual component of type T, but it is accessible only with *_sync_component().
+    pub fn add_sync_component<T : Any + Component + Send + Sync>(&self, component : T) {
+        self.components.borrow_mut().insert(named_type_id::<T>(), Box::new(Arc::new(RwLock::new(component))));
+        self.mark_changed();
+    }
+
+    /// Shared handle to sync component, to read or write it from other threads.
+    /// Writes through it are not tracked, so entity is considered changed from now on, see `change_tick`.
+    pub fn sync_component<T : Any + Component + Send + Sync>(&self) -> Arc<RwLock<T>> {
+        self.shared.set(true);
+        self.sync_lock::<T>()
+    }
+
+    fn sync_lock<T : Any + Component + Send + Sync>(&self) -> Arc<RwLock<T>> {
+        let components = self.components.borrow();
+        let component = match components.get(&TypeId::of::<T>()) {
+            Some(component) => component,
+            None => panic!("Entity {} has no component {} or it is already borrowed", self.id, name_of::<T>())
+        };
+        match component.downcast_ref::<Arc<RwLock<T>>>() {
+            Some(lock) => lock.clone(),
+            None => panic!("Component {} was added with add_component, not add_sync_component", name_of::<T>())
+        }
+    }
+
+    /// Lock sync component for reading, waiting for writers in other threads.
+    pub fn read_sync_component<T : Any + Component + Send + Sync>(&self) -> SyncReadGuard<T> {
+        let lock = self.sync_lock::<T>();
+        let guard = lock.read().unwrap_or_else(|error| error.into_inner());
+        // guard lives in the same struct as the Arc and is dropped first
+        let guard = unsafe { ::std::mem::transmute::<RwLockReadGuard<T>, RwLockReadGuard<'static, T>>(guard) };
+        SyncReadGuard {
+            guard,
+            _lock : lock
+        }
+    }
+
+    /// Lock sync component for writing, waiting for other threads.
+    pub fn write_sync_component<T : Any + Component + Send + Sync>(&self) -> SyncWriteGuard<T> {
+        let lock = self.sync_lock::<T>();
+        self.mark_changed();
+        let guard = lock.write().unwrap_or_else(|error| error.into_inner());
+        // guard lives in the same struct as the Arc and is dropped first
+        let guard = unsafe { ::std::mem::transmute::<RwLockWriteGuard<T>, RwLockWriteGuard<'static, T>>(guard) };
+        SyncWriteGuard {
+            guard,
+            _lock : lock
+        }
+    }
+
+    /// Like get_component(), but None if entity has no such component or it is borrowed.
+    /// Sync components are None too, they are accessible with *_sync_component().
+    pub fn try_get_component<T : Any + Component>(&self) -> Option<ComponentGuard<'_, T>> {
+        if self.components.borrow().get(&TypeId::of::<T>()).is_some_and(|component| component.is::<T>()) {
+            Some(self.get_component::<T>())
+        } else {
+            None
         }
     }
 
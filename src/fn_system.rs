@@ -0,0 +1,144 @@
+use std::marker::PhantomData;
+
+use entity::Entity;
+use component::Component;
+use aspect::Aspect;
+use type_info::named_type_id;
+use system::System;
+use world::{World, WorldHandle, SystemHandle};
+
+/// System made of a closure, see `World::add_fn_system`.
+pub struct FnSystem<F> {
+    aspect : Aspect,
+    f      : F
+}
+
+impl<F> System for FnSystem<F> where F : FnMut(&mut Entity, &mut WorldHandle) {
+    fn aspect(&self) -> Aspect {
+        self.aspect.clone()
+    }
+
+    fn process_w(&mut self, entity : &mut Entity, world : &mut WorldHandle) {
+        (self.f)(entity, world);
+    }
+}
+
+/// Marker for `&mut T` parameter of typed closure system.
+pub struct Mut<T>(PhantomData<T>);
+/// Marker for `&T` parameter of typed closure system.
+pub struct Ref<T>(PhantomData<T>);
+
+/// Closure, taking references to components, like `|pos : &mut Position, vel : &Velocity|`.
+///
+/// `Params` is a tuple of `Mut` and `Ref` markers, it is inferred from the closure.
+pub trait ComponentsFn<Params> {
+    fn aspect() -> Aspect;
+    fn call(&mut self, entity : &Entity);
+}
+
+macro_rules! param_type {
+    (Mut $t:ident) => { &mut $t };
+    (Ref $t:ident) => { &$t };
+}
+
+macro_rules! param_ref {
+    (Mut $var:ident) => { &mut *$var };
+    (Ref $var:ident) => { &*$var };
+}
+
+macro_rules! impl_components_fn {
+    ($( $var:ident : $kind:ident $t:ident ),*) => {
+        impl<Func, $( $t : Component ),*> ComponentsFn<($( $kind<$t>, )*)> for Func
+            where Func : FnMut($( param_type!($kind $t) ),*) {
+            fn aspect() -> Aspect {
+                Aspect::new(vec![$( named_type_id::<$t>() ),*], Vec::new())
+            }
+
+            #[allow(unused_mut)]
+            fn call(&mut self, entity : &Entity) {
+                $( let mut $var = entity.get_component::<$t>(); )*
+                self($( param_ref!($kind $var) ),*);
+            }
+        }
+    }
+}
+
+impl_components_fn!(a : Mut A);
+impl_components_fn!(a : Ref A);
+
+impl_components_fn!(a : Mut A, b : Mut B);
+impl_components_fn!(a : Mut A, b : Ref B);
+impl_components_fn!(a : Ref A, b : Mut B);
+impl_components_fn!(a : Ref A, b : Ref B);
+
+impl_components_fn!(a : Mut A, b : Mut B, c : Mut C);
+impl_components_fn!(a : Mut A, b : Mut B, c : Ref C);
+impl_components_fn!(a : Mut A, b : Ref B, c : Mut C);
+impl_components_fn!(a : Mut A, b : Ref B, c : Ref C);
+impl_components_fn!(a : Ref A, b : Mut B, c : Mut C);
+impl_components_fn!(a : Ref A, b : Mut B, c : Ref C);
+impl_components_fn!(a : Ref A, b : Ref B, c : Mut C);
+impl_components_fn!(a : Ref A, b : Ref B, c : Ref C);
+
+impl_components_fn!(a : Mut A, b : Mut B, c : Mut C, d : Mut D);
+impl_components_fn!(a : Mut A, b : Mut B, c : Mut C, d : Ref D);
+impl_components_fn!(a : Mut A, b : Mut B, c : Ref C, d : Mut D);
+impl_components_fn!(a : Mut A, b : Mut B, c : Ref C, d : Ref D);
+impl_components_fn!(a : Mut A, b : Ref B, c : Mut C, d : Mut D);
+impl_components_fn!(a : Mut A, b : Ref B, c : Mut C, d : Ref D);
+impl_components_fn!(a : Mut A, b : Ref B, c : Ref C, d : Mut D);
+impl_components_fn!(a : Mut A, b : Ref B, c : Ref C, d : Ref D);
+impl_components_fn!(a : Ref A, b : Mut B, c : Mut C, d : Mut D);
+impl_components_fn!(a : Ref A, b : Mut B, c : Mut C, d : Ref D);
+impl_components_fn!(a : Ref A, b : Mut B, c : Ref C, d : Mut D);
+impl_components_fn!(a : Ref A, b : Mut B, c : Ref C, d : Ref D);
+impl_components_fn!(a : Ref A, b : Ref B, c : Mut C, d : Mut D);
+impl_components_fn!(a : Ref A, b : Ref B, c : Mut C, d : Ref D);
+impl_components_fn!(a : Ref A, b : Ref B, c : Ref C, d : Mut D);
+impl_components_fn!(a : Ref A, b : Ref B, c : Ref C, d : Ref D);
+
+/// System made of a closure over components, see `World::add_components_system`.
+pub struct ComponentsSystem<F, Params> {
+    f      : F,
+    params : PhantomData<fn() -> Params>
+}
+
+impl<F, Params> System for ComponentsSystem<F, Params> where F : ComponentsFn<Params> {
+    fn aspect(&self) -> Aspect {
+        F::aspect()
+    }
+
+    fn process_one(&mut self, entity : &mut Entity) {
+        self.f.call(entity);
+    }
+}
+
+impl World {
+    /// Add system, processing each entity with given closure.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// world.add_fn_system(aspect_all!(Position, Velocity), |entity, world| {
+    ///     let mut pos = entity.get_component::<Position>();
+    ///     pos.x += entity.get_component::<Velocity>().x * world.delta;
+    /// });
+    /// ```
+    pub fn add_fn_system<F>(&mut self, aspect : Aspect, f : F) -> SystemHandle
+        where F : FnMut(&mut Entity, &mut WorldHandle) + 'static {
+        self.set_system(FnSystem { aspect, f })
+    }
+
+    /// Add system, processing components of each entity with given closure.
+    /// Aspect is made of closure parameter types, up to four components.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// world.add_components_system(|pos : &mut Position, vel : &Velocity| {
+    ///     pos.x += vel.x;
+    /// });
+    /// ```
+    pub fn add_components_system<Params, F>(&mut self, f : F) -> SystemHandle
+        where F : ComponentsFn<Params> + 'static, Params : 'static {
+        self.set_system(ComponentsSystem { f, params : PhantomData })
+    }
+}
@@ -0,0 +1,145 @@
+//! Textual reports about entities, their components and systems, for debugging.
+
+use std::any::{Any, TypeId};
+use std::fmt::{self, Debug};
+
+use world::{World, SystemHandle};
+use entity::Entity;
+use aspect::AspectReport;
+use type_info::type_name;
+
+/// See `World::inspect_entity`.
+#[derive(Clone, Debug)]
+pub struct EntityReport {
+    pub id         : i32,
+    pub components : Vec<ComponentReport>,
+    pub systems    : Vec<SystemMembership>
+}
+
+#[derive(Clone, Debug)]
+pub struct ComponentReport {
+    /// Registered name, or TypeId for unregistered types.
+    pub name    : String,
+    pub type_id : TypeId,
+    /// Debug output, if type is registered with `World::register_debug`.
+    pub value   : Option<String>
+}
+
+/// How entity relates to one system, as of the last refresh.
+#[derive(Clone, Debug)]
+pub struct SystemMembership {
+    pub handle        : SystemHandle,
+    pub name          : String,
+    /// Entity is processed by the system.
+    pub processed     : bool,
+    /// Indices of data aspects, containing the entity.
+    pub data_aspects  : Vec<usize>,
+    /// Why entity does or does not satisfy the system aspect now.
+    pub aspect        : AspectReport
+}
+
+/// See `World::inspect`.
+#[derive(Clone, Debug)]
+pub struct WorldReport {
+    pub entities : Vec<EntityReport>
+}
+
+impl World {
+    /// Register component type for `inspect`, to show its value.
+    pub fn register_debug<T : Any + Debug>(&mut self) {
+        self.registry.register_debug::<T>();
+    }
+
+    /// Report about all entities, in id order.
+    pub fn inspect(&self) -> WorldReport {
+        WorldReport {
+            entities : self.entities.values().map(|entity| self.entity_report(entity)).collect()
+        }
+    }
+
+    /// Report about the entity: its components and systems, which do or do not process it.
+    pub fn inspect_entity(&self, id : i32) -> Option<EntityReport> {
+        self.entities.get(id as usize).map(|entity| self.entity_report(entity))
+    }
+
+    /// Why entity is or is not processed by the system.
+    /// None if there is no such entity.
+    pub fn why_not(&self, system : SystemHandle, entity : i32) -> Option<AspectReport> {
+        let data = match self.systems.iter().find(|s| s.0.handle == system) {
+            Some(system) => &system.0,
+            None => panic!("No system with handle {:?}", system)
+        };
+        self.entities.get(entity as usize).map(|entity| data.aspect.explain_with(entity, &|type_id| self.component_name(type_id)))
+    }
+
+    pub(crate) fn component_name(&self, type_id : TypeId) -> String {
+        match self.registry.get(type_id) {
+            Some(registration) => registration.name.clone(),
+            None => type_name(type_id)
+        }
+    }
+
+    fn entity_report(&self, entity : &Entity) -> EntityReport {
+        let mut component_reports = entity.components.borrow().iter().map(|(type_id, component)| {
+            let registration = self.registry.get(*type_id);
+            ComponentReport {
+                name    : self.component_name(*type_id),
+                type_id : *type_id,
+                value   : registration.and_then(|r| r.debug).map(|debug| debug(&**component))
+            }
+        }).collect::<Vec<_>>();
+        component_reports.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let systems = self.systems.iter().map(|(data, selected)| {
+            SystemMembership {
+                handle       : data.handle,
+                name         : data.system.get_name(),
+                processed    : selected.entity_set.contains(&entity.id),
+                data_aspects : selected.data_set.iter().enumerate()
+                    .filter(|&(_, set)| set.contains(&entity.id))
+                    .map(|(n, _)| n)
+                    .collect(),
+                aspect       : data.aspect.explain_with(entity, &|type_id| self.component_name(type_id))
+            }
+        }).collect();
+
+        EntityReport {
+            id         : entity.id,
+            components : component_reports,
+            systems
+        }
+    }
+}
+
+impl fmt::Display for EntityReport {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "entity {}", self.id)?;
+        for component in self.components.iter() {
+            match component.value {
+                Some(ref value) => writeln!(f, "  {}: {}", component.name, value)?,
+                None => writeln!(f, "  {}", component.name)?
+            }
+        }
+        for system in self.systems.iter() {
+            if system.processed {
+                write!(f, "  + {}", system.name)?;
+            } else {
+                write!(f, "  - {}, {}", system.name, system.aspect)?;
+            }
+            if !system.data_aspects.is_empty() {
+                write!(f, ", in data aspects: {:?}", system.data_aspects)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for WorldReport {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        for entity in self.entities.iter() {
+            write!(f, "{}", entity)?;
+        }
+        Ok(())
+    }
+}
@@ -62,21 +62,68 @@ impl System for MoveSystem {
 ```
 
 */
-#![cfg_attr(feature = "prof", feature(core_intrinsics))]
-
-#[cfg(feature = "prof")]
-#[macro_use] extern crate tinyprof;
-
-#[cfg(not(feature = "prof"))]
-macro_rules! profile_region { ($name:expr) => {    } }
-
 extern crate time;
 extern crate vec_map;
 
+#[cfg(feature = "derive")]
+extern crate tinyecs_derive;
+#[cfg(feature = "serde")]
+#[macro_use] extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "serde")]
+extern crate bincode;
+
 mod entity;
 mod component;
 mod world;
 mod system;
 mod aspect;
+mod resource;
+mod registry;
+mod snapshot;
+mod checksum;
+mod clock;
+mod timer;
+mod state;
+mod fn_system;
+mod query;
+mod profile;
+mod trace;
+mod inspect;
+mod type_info;
+mod reflect;
+mod console;
+mod prediction;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "serde")]
+mod scene;
+#[cfg(feature = "serde")]
+mod replication;
+#[cfg(feature = "remote")]
+mod remote;
 
 pub use world::*;
+#[cfg(feature = "derive")]
+pub use tinyecs_derive::*;
+pub use snapshot::*;
+pub use checksum::*;
+pub use timer::*;
+pub use fn_system::*;
+pub use query::*;
+pub use profile::*;
+pub use trace::*;
+pub use inspect::*;
+pub use type_info::*;
+pub use reflect::*;
+pub use console::*;
+pub use prediction::*;
+#[cfg(feature = "remote")]
+pub use remote::*;
+#[cfg(feature = "serde")]
+pub use serialize::*;
+#[cfg(feature = "serde")]
+pub use scene::*;
+#[cfg(feature = "serde")]
+pub use replication::*;
@@ -0,0 +1,237 @@
+//! Client-side prediction and reconciliation with authoritative server state.
+//!
+//! Client runs its simulation ahead of the server with local inputs. When server state
+//! for some tick arrives, the world is rewound to it and inputs, not yet processed
+//! by server, are applied again:
+//!
+//! ```ignore
+//! let mut prediction = Prediction::new(&world, SystemGroup::FIXED, 1.0 / 60.0);
+//! prediction.on_correction::<Position, _>(|entity, predicted, corrected| {
+//!     // keep drawing the entity near the predicted position, decaying the offset over time
+//!     entity.add_component(VisualOffset(predicted.0 - corrected.0));
+//! });
+//!
+//! // every tick
+//! let tick = prediction.predict(&mut world, input);
+//! send_input(tick, input);
+//!
+//! // on server update, acknowledging inputs up to server_tick
+//! prediction.reconcile(&mut world, server_tick, |world| {
+//!     replica.apply(world, &message).unwrap();
+//! });
+//! ```
+//!
+//! Input of the tick is available to systems as a resource of its type.
+//! All components and resources of the world, including the input, must be registered
+//! with `register_clone`, see `World::snapshot`.
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+
+use component::Component;
+use entity::Entity;
+use snapshot::WorldSnapshot;
+use system::SystemGroup;
+use world::World;
+
+/// Inputs kept by `Prediction` by default, older ones are dropped even if not acknowledged.
+pub const INPUT_HISTORY : usize = 128;
+
+/// Inputs by the tick they were applied at.
+pub struct InputBuffer<I> {
+    inputs   : VecDeque<(u64, I)>,
+    capacity : usize
+}
+
+impl<I> InputBuffer<I> {
+    pub fn new(capacity : usize) -> InputBuffer<I> {
+        InputBuffer {
+            inputs : VecDeque::with_capacity(capacity),
+            capacity
+        }
+    }
+
+    /// Add input of the next tick, the oldest input is dropped when buffer is full.
+    /// Panics if tick is not greater than the latest one.
+    pub fn push(&mut self, tick : u64, input : I) {
+        if let Some(latest) = self.latest_tick() {
+            assert!(tick > latest, "Input for tick {} pushed after tick {}", tick, latest);
+        }
+        self.inputs.push_back((tick, input));
+        while self.inputs.len() > self.capacity {
+            self.inputs.pop_front();
+        }
+    }
+
+    pub fn get(&self, tick : u64) -> Option<&I> {
+        self.inputs.iter().find(|i| i.0 == tick).map(|i| &i.1)
+    }
+
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.inputs.back().map(|i| i.0)
+    }
+
+    /// Drop inputs up to and including the tick, server has processed them.
+    pub fn acknowledge(&mut self, tick : u64) {
+        while self.inputs.front().is_some_and(|i| i.0 <= tick) {
+            self.inputs.pop_front();
+        }
+    }
+
+    /// Inputs after the tick, oldest first.
+    pub fn since(&self, tick : u64) -> impl Iterator<Item = (u64, &I)> {
+        self.inputs.iter().filter(move |i| i.0 > tick).map(|i| (i.0, &i.1))
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+impl World {
+    /// Restore confirmed state and update systems of the group once for each input,
+    /// with the input inserted as a resource.
+    /// Returns the number of resimulated ticks.
+    pub fn resimulate<'a, I, It>(&mut self, confirmed : &WorldSnapshot, inputs : It, group : SystemGroup, step : f32) -> u32
+        where I : Any + Clone, It : IntoIterator<Item = &'a I> {
+        self.restore(confirmed);
+
+        let mut ticks = 0;
+        for input in inputs {
+            self.resources.insert(input.clone());
+            self.update_group(group, step);
+            ticks += 1;
+        }
+        ticks
+    }
+}
+
+/// Values of some component type, captured before reconciliation.
+trait Correction {
+    fn capture(&self, world : &World) -> Box<dyn Any>;
+    fn apply(&mut self, world : &World, predicted : Box<dyn Any>);
+}
+
+struct ComponentCorrection<T, F> {
+    hook      : F,
+    component : PhantomData<fn(&T)>
+}
+
+impl<T, F> Correction for ComponentCorrection<T, F>
+    where T : Component + Clone + PartialEq, F : FnMut(&Entity, &T, &T) {
+    fn capture(&self, world : &World) -> Box<dyn Any> {
+        let values = world.entities.iter().filter_map(|(id, entity)| {
+            let components = entity.components.borrow();
+            components.get(&TypeId::of::<T>())
+                .and_then(|component| component.downcast_ref::<T>())
+                .map(|component| (id as i32, component.clone()))
+        }).collect::<HashMap<i32, T>>();
+        Box::new(values)
+    }
+
+    fn apply(&mut self, world : &World, predicted : Box<dyn Any>) {
+        let predicted = predicted.downcast::<HashMap<i32, T>>().unwrap();
+        let mut ids = predicted.keys().cloned().collect::<Vec<_>>();
+        ids.sort();
+        for id in ids {
+            let entity = match world.entities.get(id as usize) {
+                Some(entity) => entity,
+                None => continue
+            };
+            let corrected = match entity.try_get_component::<T>() {
+                Some(corrected) => (*corrected).clone(),
+                None => continue
+            };
+            if predicted[&id] != corrected {
+                (self.hook)(entity, &predicted[&id], &corrected);
+            }
+        }
+    }
+}
+
+/// Predicts world with local inputs, and reconciles it with server state.
+pub struct Prediction<I> {
+    pub inputs     : InputBuffer<I>,
+    group          : SystemGroup,
+    step           : f32,
+    tick           : u64,
+    confirmed      : WorldSnapshot,
+    confirmed_tick : u64,
+    corrections    : Vec<Box<dyn Correction>>
+}
+
+impl<I : Any + Clone> Prediction<I> {
+    /// Current state of the world is confirmed state of tick 0.
+    /// Each predicted tick updates systems of the group with the constant step.
+    pub fn new(world : &World, group : SystemGroup, step : f32) -> Prediction<I> {
+        Prediction {
+            inputs         : InputBuffer::new(INPUT_HISTORY),
+            group,
+            step,
+            tick           : 0,
+            confirmed      : world.snapshot(),
+            confirmed_tick : 0,
+            corrections    : vec![]
+        }
+    }
+
+    /// The last predicted tick.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// The tick of the last server state, passed to `reconcile`.
+    pub fn confirmed_tick(&self) -> u64 {
+        self.confirmed_tick
+    }
+
+    /// Call hook for each entity, whose component changed by reconciliation,
+    /// with predicted and corrected values. Hook must not borrow the component from the entity.
+    pub fn on_correction<T, F>(&mut self, hook : F)
+        where T : Component + Clone + PartialEq, F : FnMut(&Entity, &T, &T) + 'static {
+        self.corrections.push(Box::new(ComponentCorrection { hook, component : PhantomData }));
+    }
+
+    /// Apply input and simulate the next tick. Returns the tick, input was applied at.
+    pub fn predict(&mut self, world : &mut World, input : I) -> u64 {
+        self.tick += 1;
+        self.inputs.push(self.tick, input.clone());
+        world.resources.insert(input);
+        world.update_group(self.group, self.step);
+        self.tick
+    }
+
+    /// Server processed inputs up to the tick, `apply_server_state` brings its state
+    /// into the world, rewound to the previous confirmed state.
+    /// Then all inputs after the tick are applied again.
+    /// Returns the number of resimulated ticks.
+    ///
+    /// Server state for a tick not after `confirmed_tick`, like a late or duplicate packet,
+    /// is stale: it is ignored, world is not touched and 0 is returned.
+    pub fn reconcile<F>(&mut self, world : &mut World, tick : u64, apply_server_state : F) -> u32
+        where F : FnOnce(&mut World) {
+        if tick <= self.confirmed_tick {
+            return 0;
+        }
+        let predicted = self.corrections.iter().map(|c| c.capture(world)).collect::<Vec<_>>();
+
+        world.restore(&self.confirmed);
+        apply_server_state(world);
+        self.confirmed = world.snapshot();
+        self.confirmed_tick = tick;
+        self.inputs.acknowledge(tick);
+        self.tick = self.tick.max(tick);
+
+        let ticks = world.resimulate(&self.confirmed, self.inputs.since(tick).map(|i| i.1), self.group, self.step);
+
+        for (correction, predicted) in self.corrections.iter_mut().zip(predicted) {
+            correction.apply(world, predicted);
+        }
+        ticks
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use world::SystemHandle;
+use trace::Trace;
+
+/// How many last calls are kept for averages and percentiles.
+pub const STATS_WINDOW : usize = 120;
+
+/// Timed part of system's frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    BeginFrame,
+    Process,
+    EndFrame,
+    Added,
+    Removed
+}
+
+impl Phase {
+    pub const ALL : [Phase; 5] = [Phase::BeginFrame, Phase::Process, Phase::EndFrame, Phase::Added, Phase::Removed];
+
+    /// Category of this phase spans in trace.
+    pub fn category(&self) -> &'static str {
+        match *self {
+            Phase::BeginFrame => "begin_frame",
+            Phase::Process => "process",
+            Phase::EndFrame => "end_frame",
+            Phase::Added => "on_added",
+            Phase::Removed => "on_removed"
+        }
+    }
+}
+
+/// Durations of last STATS_WINDOW calls of one phase.
+#[derive(Clone, Debug, Default)]
+pub struct PhaseStats {
+    samples : VecDeque<Duration>,
+    calls   : u64
+}
+
+impl PhaseStats {
+    pub(crate) fn record(&mut self, duration : Duration) {
+        if self.samples.len() == STATS_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration);
+        self.calls += 1;
+    }
+
+    /// All calls, including ones out of window.
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.back().cloned()
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::new(0, 0);
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples.iter().max().cloned().unwrap_or_default()
+    }
+
+    /// Duration, longer than `percent` of calls in window, like `percentile(99.0)`.
+    pub fn percentile(&self, percent : f32) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::new(0, 0);
+        }
+        let mut samples = self.samples.iter().cloned().collect::<Vec<_>>();
+        samples.sort();
+        let percent = percent.clamp(0.0, 100.0);
+        let index = (percent / 100.0 * (samples.len() - 1) as f32).round() as usize;
+        samples[index]
+    }
+}
+
+/// Timings of one system, see `World::system_stats`.
+#[derive(Clone, Debug)]
+pub struct SystemStats {
+    pub name   : String,
+    pub handle : SystemHandle,
+    phases     : [PhaseStats; 5]
+}
+
+impl SystemStats {
+    pub(crate) fn new(name : String, handle : SystemHandle) -> SystemStats {
+        SystemStats {
+            name,
+            handle,
+            phases : Default::default()
+        }
+    }
+
+    pub fn phase(&self, phase : Phase) -> &PhaseStats {
+        &self.phases[phase as usize]
+    }
+
+    /// Call f and record its duration, also as a span in trace, if given.
+    pub(crate) fn time<R, F : FnOnce() -> R>(&mut self, phase : Phase, trace : Option<&mut Trace>, f : F) -> R {
+        let start = Instant::now();
+        let result = f();
+        let duration = start.elapsed();
+        self.phases[phase as usize].record(duration);
+        if let Some(trace) = trace {
+            trace.span(&self.name, phase.category(), start, duration);
+        }
+        result
+    }
+}
@@ -0,0 +1,200 @@
+//! Typed data aspects.
+//!
+//! Instead of indexing `DataList`, system asks for components of additional entities:
+//!
+//! ```ignore
+//! #[system]
+//! fn follow(pos : &mut Position, camera : Single<(&Camera, &Position)>, enemies : Query<&Enemy>) {
+//!     let (ref camera, ref camera_pos) = *camera;
+//!     for enemy in enemies.iter() {
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::error::Error;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use aspect::Aspect;
+use component::Component;
+use entity::{Entity, ComponentGuard};
+use system::DataList;
+
+/// `&T`, `&mut T` or tuple of them, what query fetches from each entity.
+///
+/// Both `&T` and `&mut T` are fetched as ComponentGuard.
+pub trait QueryData {
+    type Item<'a>;
+
+    /// Aspect of entities with all of fetched components.
+    fn aspect() -> Aspect;
+
+    /// Entity should satisfy aspect().
+    fn fetch(entity : &Entity) -> Self::Item<'_>;
+}
+
+impl<T : Component> QueryData for &T {
+    type Item<'a> = ComponentGuard<'a, T>;
+
+    fn aspect() -> Aspect {
+        Aspect::all::<T>()
+    }
+    fn fetch(entity : &Entity) -> Self::Item<'_> {
+        entity.get_component::<T>()
+    }
+}
+
+impl<T : Component> QueryData for &mut T {
+    type Item<'a> = ComponentGuard<'a, T>;
+
+    fn aspect() -> Aspect {
+        Aspect::all::<T>()
+    }
+    fn fetch(entity : &Entity) -> Self::Item<'_> {
+        entity.get_component::<T>()
+    }
+}
+
+macro_rules! impl_query_data_tuple {
+    ($($t:ident),*) => {
+        impl<$($t : QueryData),*> QueryData for ($($t,)*) {
+            type Item<'a> = ($($t::Item<'a>,)*);
+
+            fn aspect() -> Aspect {
+                let mut aspect = Aspect::new(vec![], vec![]);
+                $( aspect.accept_types.extend($t::aspect().accept_types); )*
+                aspect
+            }
+            fn fetch(entity : &Entity) -> Self::Item<'_> {
+                ($($t::fetch(entity),)*)
+            }
+        }
+    }
+}
+
+impl_query_data_tuple!(A);
+impl_query_data_tuple!(A, B);
+impl_query_data_tuple!(A, B, C);
+impl_query_data_tuple!(A, B, C, D);
+
+/// Any number of entities, matched by data aspect.
+pub struct Query<'a, Q : QueryData> {
+    entities : &'a [&'a mut Entity],
+    _query   : PhantomData<Q>
+}
+
+impl<'a, Q : QueryData> Query<'a, Q> {
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Fetched components of each entity.
+    pub fn iter(&self) -> impl Iterator<Item = Q::Item<'a>> + 'a {
+        self.entities.iter().map(|entity| Q::fetch(entity))
+    }
+
+    /// Entities with their fetched components.
+    pub fn iter_entities(&self) -> impl Iterator<Item = (&'a Entity, Q::Item<'a>)> + 'a {
+        self.entities.iter().map(|entity| (&**entity, Q::fetch(entity)))
+    }
+}
+
+/// Exactly one entity, matched by data aspect. Derefs to its fetched components.
+pub struct Single<'a, Q : QueryData> {
+    pub entity : &'a Entity,
+    pub item   : Q::Item<'a>
+}
+
+impl<'a, Q : QueryData> Deref for Single<'a, Q> {
+    type Target = Q::Item<'a>;
+
+    fn deref(&self) -> &Q::Item<'a> {
+        &self.item
+    }
+}
+impl<'a, Q : QueryData> DerefMut for Single<'a, Q> {
+    fn deref_mut(&mut self) -> &mut Q::Item<'a> {
+        &mut self.item
+    }
+}
+
+/// No entities or one entity, matched by data aspect.
+pub type OptionalSingle<'a, Q> = Option<Single<'a, Q>>;
+
+/// Data aspect matched unexpected number of entities.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    /// No data aspect with this index.
+    NoDataAspect(usize),
+    NoEntities,
+    TooManyEntities(usize)
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            QueryError::NoDataAspect(n) => write!(f, "system has no data aspect #{}", n),
+            QueryError::NoEntities => write!(f, "expected single entity, found none"),
+            QueryError::TooManyEntities(n) => write!(f, "expected single entity, found {}", n)
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+thread_local! {
+    /// Systems and parameters, already reported by `report_query_error`.
+    static REPORTED : RefCell<HashSet<(String, String)>> = RefCell::new(HashSet::new());
+}
+
+/// Used by `#[system]`, when its query parameter can not be fetched and the system skips the frame.
+/// Error is written to stderr once per system parameter.
+#[doc(hidden)]
+pub fn report_query_error(system : &str, param : &str, error : &QueryError) {
+    let new = REPORTED.with(|reported| reported.borrow_mut().insert((system.to_string(), param.to_string())));
+    if new {
+        eprintln!("{} skipped, parameter {}: {}", system, param, error);
+    }
+}
+
+impl<'b> DataList<'b> {
+    fn nth(&self, n : usize) -> Result<&[&'b mut Entity], QueryError> {
+        self.data.get(n).map(|entities| &entities[..]).ok_or(QueryError::NoDataAspect(n))
+    }
+
+    /// Entities of n-th data aspect.
+    pub fn query<Q : QueryData>(&self, n : usize) -> Result<Query<'_, Q>, QueryError> {
+        Ok(Query {
+            entities : self.nth(n)?,
+            _query   : PhantomData
+        })
+    }
+
+    /// The only entity of n-th data aspect.
+    pub fn single<Q : QueryData>(&self, n : usize) -> Result<Single<'_, Q>, QueryError> {
+        match self.optional_single(n)? {
+            Some(single) => Ok(single),
+            None => Err(QueryError::NoEntities)
+        }
+    }
+
+    /// The only entity of n-th data aspect, if any.
+    pub fn optional_single<Q : QueryData>(&self, n : usize) -> Result<OptionalSingle<'_, Q>, QueryError> {
+        let entities = self.nth(n)?;
+        match entities.len() {
+            0 => Ok(None),
+            1 => Ok(Some(Single {
+                entity : &*entities[0],
+                item   : Q::fetch(&*entities[0])
+            })),
+            n => Err(QueryError::TooManyEntities(n))
+        }
+    }
+}
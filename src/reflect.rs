@@ -0,0 +1,314 @@
+//! Runtime access to component fields by name, for editors and consoles.
+//!
+//! ```ignore
+//! #[derive(Reflect)]
+//! struct Health { hp : i32 }
+//!
+//! world.register_reflect::<Health>();
+//! entity.set_path(world.registry(), "Health.hp", ReflectValue::Int(50)).unwrap();
+//! ```
+
+use std::any::{Any, TypeId};
+use std::convert::TryFrom;
+use std::fmt;
+use std::error::Error;
+use std::ops::{Deref, DerefMut};
+
+use entity::Entity;
+use registry::{ComponentRegistry, ReflectFns};
+
+/// Field of reflected type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldInfo {
+    pub name      : &'static str,
+    pub type_name : &'static str
+}
+
+/// Value of a leaf field: number, bool or string.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReflectValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String)
+}
+
+impl ReflectValue {
+    /// Value from user input: `true`, `50`, `0.5`, anything else is a string.
+    pub fn parse(s : &str) -> ReflectValue {
+        if let Ok(b) = s.parse::<bool>() {
+            return ReflectValue::Bool(b);
+        }
+        if let Ok(n) = s.parse::<i64>() {
+            return ReflectValue::Int(n);
+        }
+        if let Ok(n) = s.parse::<f64>() {
+            return ReflectValue::Float(n);
+        }
+        ReflectValue::String(s.to_string())
+    }
+}
+
+impl fmt::Display for ReflectValue {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReflectValue::Int(n) => write!(f, "{}", n),
+            ReflectValue::Float(n) => write!(f, "{}", n),
+            ReflectValue::Bool(b) => write!(f, "{}", b),
+            ReflectValue::String(ref s) => write!(f, "{:?}", s)
+        }
+    }
+}
+
+/// Types with fields, accessible by name at runtime. Derivable with `#[derive(Reflect)]`.
+///
+/// Structs expose named fields, leaf types (numbers, bool, String) expose a value.
+pub trait Reflect : Any {
+    fn fields() -> Vec<FieldInfo> where Self : Sized;
+
+    fn field_names(&self) -> Vec<&'static str> {
+        vec![]
+    }
+    fn field(&self, _name : &str) -> Option<&dyn Reflect> {
+        None
+    }
+    fn field_mut(&mut self, _name : &str) -> Option<&mut dyn Reflect> {
+        None
+    }
+
+    /// Value of leaf type.
+    fn get_value(&self) -> Option<ReflectValue> {
+        None
+    }
+    /// Set value of leaf type, on mismatch error is the expected type.
+    fn set_value(&mut self, _value : ReflectValue) -> Result<(), &'static str> {
+        Err("struct")
+    }
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Why reflected access failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReflectError {
+    /// No component with this name, or it is not registered with `World::register_reflect`.
+    NoComponent(String),
+    /// Component is borrowed at the moment, with `get_component` or another `reflect_component`.
+    Borrowed(String),
+    NoField(String),
+    /// Field is a struct, not a value.
+    NotAValue(String),
+    TypeMismatch { path : String, expected : &'static str }
+}
+
+impl fmt::Display for ReflectError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReflectError::NoComponent(ref name) => write!(f, "no reflected component \"{}\"", name),
+            ReflectError::Borrowed(ref name) => write!(f, "component \"{}\" is borrowed", name),
+            ReflectError::NoField(ref path) => write!(f, "no field \"{}\"", path),
+            ReflectError::NotAValue(ref path) => write!(f, "\"{}\" is not a value", path),
+            ReflectError::TypeMismatch { ref path, expected } => write!(f, "\"{}\" expects {}", path, expected)
+        }
+    }
+}
+
+impl Error for ReflectError {}
+
+impl dyn Reflect {
+    /// Nested field by dot separated path, like `position.x`. Empty path is self.
+    pub fn path(&self, path : &str) -> Result<&dyn Reflect, ReflectError> {
+        let mut current = self;
+        for name in path.split('.').filter(|name| !name.is_empty()) {
+            current = current.field(name).ok_or_else(|| ReflectError::NoField(path.to_string()))?;
+        }
+        Ok(current)
+    }
+
+    pub fn path_mut(&mut self, path : &str) -> Result<&mut dyn Reflect, ReflectError> {
+        let mut current = self;
+        for name in path.split('.').filter(|name| !name.is_empty()) {
+            current = current.field_mut(name).ok_or_else(|| ReflectError::NoField(path.to_string()))?;
+        }
+        Ok(current)
+    }
+
+    /// Typed reference to nested field.
+    pub fn get<T : Any>(&self, path : &str) -> Result<&T, ReflectError> {
+        self.path(path)?.as_any().downcast_ref::<T>().ok_or_else(|| ReflectError::TypeMismatch {
+            path     : path.to_string(),
+            expected : ::std::any::type_name::<T>()
+        })
+    }
+
+    /// Replace nested field with value of the same type.
+    pub fn set<T : Any>(&mut self, path : &str, value : T) -> Result<(), ReflectError> {
+        let field = self.path_mut(path)?;
+        match field.as_any_mut().downcast_mut::<T>() {
+            Some(field) => {
+                *field = value;
+                Ok(())
+            },
+            None => Err(ReflectError::TypeMismatch { path : path.to_string(), expected : ::std::any::type_name::<T>() })
+        }
+    }
+
+    pub fn get_value_at(&self, path : &str) -> Result<ReflectValue, ReflectError> {
+        self.path(path)?.get_value().ok_or_else(|| ReflectError::NotAValue(path.to_string()))
+    }
+
+    pub fn set_value_at(&mut self, path : &str, value : ReflectValue) -> Result<(), ReflectError> {
+        self.path_mut(path)?.set_value(value).map_err(|expected| ReflectError::TypeMismatch {
+            path : path.to_string(),
+            expected
+        })
+    }
+}
+
+macro_rules! reflect_value {
+    ($t:ty, |$this:ident| $get:expr, |$value:ident| $set:expr) => {
+        impl Reflect for $t {
+            fn fields() -> Vec<FieldInfo> {
+                vec![]
+            }
+            fn get_value(&self) -> Option<ReflectValue> {
+                let $this = self;
+                Some($get)
+            }
+            #[allow(clippy::unnecessary_cast)]
+            fn set_value(&mut self, $value : ReflectValue) -> Result<(), &'static str> {
+                *self = $set?;
+                Ok(())
+            }
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+    }
+}
+
+macro_rules! reflect_int {
+    ($($t:ty),*) => {
+        $( reflect_value!($t, |this| ReflectValue::Int(*this as i64), |value| match value {
+            ReflectValue::Int(n) => <$t>::try_from(n).map_err(|_| concat!("integer in range of ", stringify!($t))),
+            _ => Err("integer")
+        }); )*
+    }
+}
+
+macro_rules! reflect_float {
+    ($($t:ty),*) => {
+        $( reflect_value!($t, |this| ReflectValue::Float(*this as f64), |value| match value {
+            ReflectValue::Float(n) => Ok(n as $t),
+            ReflectValue::Int(n) => Ok(n as $t),
+            _ => Err("number")
+        }); )*
+    }
+}
+
+reflect_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+reflect_float!(f32, f64);
+reflect_value!(bool, |this| ReflectValue::Bool(*this), |value| match value {
+    ReflectValue::Bool(b) => Ok(b),
+    _ => Err("bool")
+});
+reflect_value!(String, |this| ReflectValue::String(this.clone()), |value| match value {
+    ReflectValue::String(s) => Ok(s),
+    _ => Err("string")
+});
+
+/// Component, taken out of entity for reflected access, see `Entity::reflect_component`.
+/// Puts the component back on drop, like `ComponentGuard`.
+pub struct ReflectGuard<'a> {
+    type_id   : TypeId,
+    component : Option<Box<dyn Any>>,
+    fns       : ReflectFns,
+    entity    : &'a Entity
+}
+
+impl<'a> Deref for ReflectGuard<'a> {
+    type Target = dyn Reflect;
+    fn deref(&self) -> &(dyn Reflect + 'static) {
+        (self.fns.as_reflect)(&**self.component.as_ref().unwrap()).unwrap()
+    }
+}
+
+impl<'a> DerefMut for ReflectGuard<'a> {
+    fn deref_mut(&mut self) -> &mut (dyn Reflect + 'static) {
+        self.entity.mark_changed();
+        (self.fns.as_reflect_mut)(&mut **self.component.as_mut().unwrap()).unwrap()
+    }
+}
+
+impl<'a> Drop for ReflectGuard<'a> {
+    fn drop(&mut self) {
+        if let Some(component) = self.component.take() {
+            self.entity.components.borrow_mut().insert(self.type_id, component);
+            self.entity.borrowed.borrow_mut().remove(&self.type_id);
+        }
+    }
+}
+
+impl Entity {
+    /// Component with given name, like "Transform", registered with `ComponentRegistry::register_reflect`.
+    /// Name is resolved by `ComponentRegistry::find`.
+    pub fn reflect_component(&self, registry : &ComponentRegistry, name : &str) -> Result<ReflectGuard<'_>, ReflectError> {
+        let (type_id, fns) = match registry.find(name).and_then(|registration| Some((registration.type_id, registration.reflect?))) {
+            Some(found) => found,
+            None => return Err(ReflectError::NoComponent(name.to_string()))
+        };
+        let mut components = match self.components.try_borrow_mut() {
+            Ok(components) => components,
+            Err(_) => return Err(ReflectError::Borrowed(name.to_string()))
+        };
+        if self.borrowed.borrow().contains(&type_id) {
+            return Err(ReflectError::Borrowed(name.to_string()));
+        }
+        // sync components are stored as Arc<RwLock<T>> and can not be reflected
+        if components.get(&type_id).and_then(|component| (fns.as_reflect)(&**component)).is_none() {
+            return Err(ReflectError::NoComponent(name.to_string()));
+        }
+        let component = components.remove(&type_id).unwrap();
+        self.borrowed.borrow_mut().insert(type_id);
+        Ok(ReflectGuard {
+            type_id,
+            component : Some(component),
+            fns,
+            entity    : self
+        })
+    }
+
+    /// Value by path, starting with component name, like "Transform.position.x".
+    pub fn get_path(&self, registry : &ComponentRegistry, path : &str) -> Result<ReflectValue, ReflectError> {
+        let (component, fields) = split_path(path);
+        self.reflect_component(registry, component)?.get_value_at(fields).map_err(|error| full_path(error, component))
+    }
+
+    /// Set value by path, starting with component name, like "Health.hp".
+    pub fn set_path(&self, registry : &ComponentRegistry, path : &str, value : ReflectValue) -> Result<(), ReflectError> {
+        let (component, fields) = split_path(path);
+        self.reflect_component(registry, component)?.set_value_at(fields, value).map_err(|error| full_path(error, component))
+    }
+}
+
+fn split_path(path : &str) -> (&str, &str) {
+    match path.find('.') {
+        Some(dot) => (&path[.. dot], &path[dot + 1 ..]),
+        None => (path, "")
+    }
+}
+
+/// Errors of field access have paths inside the component, prepend the component name.
+fn full_path(error : ReflectError, component : &str) -> ReflectError {
+    let join = |path : String| if path.is_empty() { component.to_string() } else { format!("{}.{}", component, path) };
+    match error {
+        ReflectError::NoField(path) => ReflectError::NoField(join(path)),
+        ReflectError::NotAValue(path) => ReflectError::NotAValue(join(path)),
+        ReflectError::TypeMismatch { path, expected } => ReflectError::TypeMismatch { path : join(path), expected },
+        error => error
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::any::{Any, TypeId};
+use std::hash::{Hash, Hasher};
+use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+
+use component::{Storage, box_component};
+use reflect::{Reflect, FieldInfo};
+use type_info::{named_type_id, type_id_by_name, record_fields};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde_json::{self, Value};
+
+/// Calls f with component of type T, kept in a box or behind `Arc<RwLock<T>>` of sync storage.
+fn with_component<T : Any, R, F : FnOnce(&T) -> R>(component : &dyn Any, f : F) -> R {
+    if let Some(component) = component.downcast_ref::<T>() {
+        return f(component);
+    }
+    let lock = component.downcast_ref::<Arc<RwLock<T>>>().unwrap();
+    let component = lock.read().unwrap_or_else(|error| error.into_inner());
+    f(&component)
+}
+
+fn storage_of<T : Any>(component : &dyn Any) -> Storage {
+    if component.is::<Arc<RwLock<T>>>() { Storage::Sync } else { Storage::Boxed }
+}
+
+/// Functions for moving one component type in and out of serialized form.
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy)]
+pub struct SerdeFns {
+    pub serialize   : fn(&dyn Any) -> Result<Value, serde_json::Error>,
+    /// Makes component, boxed as given storage says.
+    pub deserialize : fn(Value, Storage) -> Result<Box<dyn Any>, serde_json::Error>,
+    /// Current schema version, written to saves.
+    pub version     : u32
+}
+
+/// Upgrades serialized component from one version to the next one.
+#[cfg(feature = "serde")]
+pub type Migration = Box<dyn Fn(Value) -> Result<Value, String>>;
+
+#[cfg(feature = "serde")]
+fn serialize_component<T : Any + Serialize>(component : &dyn Any) -> Result<Value, serde_json::Error> {
+    with_component::<T, _, _>(component, |component| serde_json::to_value(component))
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_component<T : Any + DeserializeOwned>(value : Value, storage : Storage) -> Result<Box<dyn Any>, serde_json::Error> {
+    let component : T = serde_json::from_value(value)?;
+    Ok(box_component(component, storage))
+}
+
+/// Makes a boxed copy of the component, behind `&dyn Any` with type of registration.
+/// Copy of sync component gets its own lock.
+pub type CloneFn = fn(&dyn Any) -> Box<dyn Any>;
+
+fn clone_component<T : Any + Clone>(component : &dyn Any) -> Box<dyn Any> {
+    box_component(with_component::<T, _, _>(component, T::clone), storage_of::<T>(component))
+}
+
+/// Feeds the component, behind `&dyn Any` with type of registration, to the hasher.
+pub type HashFn = fn(&dyn Any, &mut dyn Hasher);
+
+fn hash_component<T : Any + Hash>(component : &dyn Any, mut hasher : &mut dyn Hasher) {
+    with_component::<T, _, _>(component, |component| component.hash(&mut hasher));
+}
+
+/// Formats the component, behind `&dyn Any` with type of registration, with `Debug`.
+pub type DebugFn = fn(&dyn Any) -> String;
+
+fn debug_component<T : Any + Debug>(component : &dyn Any) -> String {
+    with_component::<T, _, _>(component, |component| format!("{:?}", component))
+}
+
+/// Makes a default value of registration type, boxed as given storage says.
+pub type DefaultFn = fn(Storage) -> Box<dyn Any>;
+
+fn default_component<T : Any + Default>(storage : Storage) -> Box<dyn Any> {
+    named_type_id::<T>();
+    box_component(T::default(), storage)
+}
+
+/// Access to fields of component, behind `&dyn Any` with type of registration.
+/// Sync components can not be reflected, casts return None for them.
+#[derive(Clone, Copy)]
+pub struct ReflectFns {
+    pub fields         : fn() -> Vec<FieldInfo>,
+    pub as_reflect     : fn(&dyn Any) -> Option<&dyn Reflect>,
+    pub as_reflect_mut : fn(&mut dyn Any) -> Option<&mut dyn Reflect>
+}
+
+fn as_reflect<T : Reflect>(component : &dyn Any) -> Option<&dyn Reflect> {
+    component.downcast_ref::<T>().map(|component| component as &dyn Reflect)
+}
+
+fn as_reflect_mut<T : Reflect>(component : &mut dyn Any) -> Option<&mut dyn Reflect> {
+    component.downcast_mut::<T>().map(|component| component as &mut dyn Reflect)
+}
+
+/// Everything world knows about one registered component or resource type.
+pub struct Registration {
+    /// Stable name, used instead of TypeId in files.
+    pub name    : String,
+    pub type_id : TypeId,
+    pub clone   : Option<CloneFn>,
+    pub hash    : Option<HashFn>,
+    pub debug   : Option<DebugFn>,
+    pub default : Option<DefaultFn>,
+    pub reflect : Option<ReflectFns>,
+    /// How components, made from saves, scenes and by default, are kept.
+    /// Set by `#[derive(Component)]` from `Component::STORAGE`.
+    pub storage : Storage,
+    #[cfg(feature = "serde")]
+    pub serde      : Option<SerdeFns>,
+    /// Sent to clients by `ReplicationServer`.
+    #[cfg(feature = "serde")]
+    pub replicated : bool,
+    /// Migrations by the version they upgrade from.
+    #[cfg(feature = "serde")]
+    pub migrations : HashMap<u32, Migration>
+}
+
+#[cfg(feature = "serde")]
+impl Registration {
+    /// Upgrade serialized data from given version to the current one, applying migrations in sequence.
+    pub fn migrate(&self, mut value : Value, from : u32) -> Result<Value, String> {
+        let current = self.serde.as_ref().map_or(0, |serde| serde.version);
+        if from > current {
+            return Err(format!("saved with version {}, newer than current version {}", from, current));
+        }
+        for version in from .. current {
+            let migration = match self.migrations.get(&version) {
+                Some(migration) => migration,
+                None => return Err(format!("no migration from version {} to {}", version, version + 1))
+            };
+            value = migration(value).map_err(|e| format!("migration from version {}: {}", version, e))?;
+        }
+        Ok(value)
+    }
+}
+
+/// Maps stable type names to component types and per-type functions.
+#[derive(Default)]
+pub struct ComponentRegistry {
+    registrations : Vec<Registration>,
+    by_type       : HashMap<TypeId, usize>,
+    by_name       : HashMap<String, usize>
+}
+
+impl ComponentRegistry {
+    pub fn new() -> ComponentRegistry {
+        ComponentRegistry::default()
+    }
+
+    /// Get registration for given type, creating it with given name if type is new
+    /// or renaming the existing one.
+    /// Panics when the name is already taken by other type.
+    pub fn register(&mut self, type_id : TypeId, name : &str) -> &mut Registration {
+        match self.by_name.get(name) {
+            Some(&index) if self.registrations[index].type_id != type_id =>
+                panic!("Component name \"{}\" is already registered for another type", name),
+            _ => {}
+        }
+        if let Some(&index) = self.by_type.get(&type_id) {
+            let old_name = ::std::mem::replace(&mut self.registrations[index].name, name.to_string());
+            self.by_name.remove(&old_name);
+            self.by_name.insert(name.to_string(), index);
+            return &mut self.registrations[index];
+        }
+
+        let index = self.registrations.len();
+        self.registrations.push(Registration {
+            name    : name.to_string(),
+            type_id,
+            clone      : None,
+            hash       : None,
+            debug      : None,
+            default    : None,
+            reflect    : None,
+            storage    : Storage::Boxed,
+            #[cfg(feature = "serde")]
+            serde      : None,
+            #[cfg(feature = "serde")]
+            replicated : false,
+            #[cfg(feature = "serde")]
+            migrations : HashMap::new()
+        });
+        self.by_type.insert(type_id, index);
+        self.by_name.insert(name.to_string(), index);
+        &mut self.registrations[index]
+    }
+
+    /// Registration of T, created under the type name, if type is not registered yet.
+    fn registration_mut<T : Any>(&mut self) -> &mut Registration {
+        let type_id = TypeId::of::<T>();
+        let name = match self.get(type_id) {
+            Some(registration) => registration.name.clone(),
+            None => ::std::any::type_name::<T>().to_string()
+        };
+        self.register(type_id, &name)
+    }
+
+    /// Register component type for world snapshots.
+    /// Type name is used as the name, unless type is registered with other name.
+    pub fn register_clone<T : Any + Clone>(&mut self) {
+        self.registration_mut::<T>().clone = Some(clone_component::<T>);
+    }
+
+    /// Register component type for world checksums.
+    /// Type name is used as the name, unless type is registered with other name.
+    pub fn register_hash<T : Any + Hash>(&mut self) {
+        self.registration_mut::<T>().hash = Some(hash_component::<T>);
+    }
+
+    /// Register component type for world inspection.
+    /// Type name is used as the name, unless type is registered with other name.
+    pub fn register_debug<T : Any + Debug>(&mut self) {
+        self.registration_mut::<T>().debug = Some(debug_component::<T>);
+    }
+
+    /// Register component type for spawning by name, like console's spawn command.
+    /// Type name is used as the name, unless type is registered with other name.
+    pub fn register_default<T : Any + Default>(&mut self) {
+        self.registration_mut::<T>().default = Some(default_component::<T>);
+    }
+
+    /// Register component type for `Entity::reflect_component`, console's `get` and `set`.
+    /// Type name is used as the name, unless type is registered with other name.
+    pub fn register_reflect<T : Reflect>(&mut self) {
+        record_fields::<T>();
+        self.registration_mut::<T>().reflect = Some(ReflectFns {
+            fields         : T::fields,
+            as_reflect     : as_reflect::<T>,
+            as_reflect_mut : as_reflect_mut::<T>
+        });
+    }
+
+    /// Register component type for world saving and loading.
+    #[cfg(feature = "serde")]
+    pub fn register_serde<T : Any + Serialize + DeserializeOwned>(&mut self, name : &str, version : u32) {
+        self.register(TypeId::of::<T>(), name).serde = Some(SerdeFns {
+            serialize   : serialize_component::<T>,
+            deserialize : deserialize_component::<T>,
+            version
+        });
+    }
+
+    /// Register component type for replication, it is serialized with serde under given name.
+    #[cfg(feature = "serde")]
+    pub fn register_replicated<T : Any + Serialize + DeserializeOwned>(&mut self, name : &str) {
+        let version = self.get(TypeId::of::<T>()).and_then(|r| r.serde.as_ref()).map_or(0, |serde| serde.version);
+        self.register_serde::<T>(name, version);
+        self.register(TypeId::of::<T>(), name).replicated = true;
+    }
+
+    /// Add migration of serialized component with given name from `from` version to `from + 1`.
+    #[cfg(feature = "serde")]
+    pub fn register_migration<F>(&mut self, name : &str, from : u32, migration : F)
+        where F : Fn(Value) -> Result<Value, String> + 'static {
+        let index = match self.by_name.get(name) {
+            Some(&index) => index,
+            None => panic!("Migration for unregistered component \"{}\"", name)
+        };
+        self.registrations[index].migrations.insert(from, Box::new(migration));
+    }
+
+    pub fn get(&self, type_id : TypeId) -> Option<&Registration> {
+        self.by_type.get(&type_id).map(|&index| &self.registrations[index])
+    }
+
+    pub fn get_by_name(&self, name : &str) -> Option<&Registration> {
+        self.by_name.get(name).map(|&index| &self.registrations[index])
+    }
+
+    /// Registration by registered name, or by short or full name of type, known to `type_id_by_name`.
+    pub fn find(&self, name : &str) -> Option<&Registration> {
+        self.get_by_name(name).or_else(|| type_id_by_name(name).and_then(|type_id| self.get(type_id)))
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<'_, Registration> {
+        self.registrations.iter()
+    }
+}
@@ -0,0 +1,327 @@
+//! Remote inspection of a running world over localhost TCP, enabled by "remote" feature.
+//!
+//! Server is polled between updates and never blocks the frame:
+//!
+//! ```ignore
+//! let mut remote = RemoteServer::localhost(7878).unwrap();
+//! loop {
+//!     remote.update(&mut world); // serves requests, then updates world unless paused
+//! }
+//! ```
+//!
+//! # Protocol
+//!
+//! Each request and response is one line of JSON. Request has a `cmd`, optional `id`,
+//! copied to the response, and command arguments:
+//!
+//! ```text
+//! {"id": 1, "cmd": "entities"}
+//! {"id": 1, "ok": true, "result": [{"id": 1, "components": ["Health", "Position"]}]}
+//!
+//! {"id": 2, "cmd": "set", "entity": 1, "path": "Health.hp", "value": 50}
+//! {"id": 2, "ok": false, "error": "no field \"Health.hp\""}
+//! ```
+//!
+//! Commands:
+//!  - `entities` - ids and component names of all entities
+//!  - `components`, `entity` - components of entity: `name`, `debug` output if registered
+//!    with `World::register_debug`, `fields` if registered with `World::register_reflect`
+//!  - `set`, `entity`, `path`, `value` - change reflected field
+//!  - `systems` - `handle`, `name`, `group`, `enabled`, `entities` and process timings
+//!    `avg_us`, `max_us`, `p99_us` of each system
+//!  - `pause`, `resume` - stop and continue updating the world
+//!  - `step`, optional `frames` - update paused world given number of frames, 1 by default
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs, SocketAddr, Ipv4Addr};
+use std::time::Duration;
+
+use serde_json::{self, Value, Map};
+
+use world::World;
+use reflect::{Reflect, ReflectValue};
+use profile::Phase;
+use type_info::type_name;
+
+struct Client {
+    stream : TcpStream,
+    input  : Vec<u8>,
+    output : Vec<u8>,
+    closed : bool
+}
+
+/// Nonblocking TCP server of the remote inspection protocol.
+pub struct RemoteServer {
+    listener : TcpListener,
+    clients  : Vec<Client>,
+    paused   : bool,
+    steps    : u32
+}
+
+impl RemoteServer {
+    /// Listen on 127.0.0.1 only, port 0 picks a free port.
+    pub fn localhost(port : u16) -> io::Result<RemoteServer> {
+        RemoteServer::bind((Ipv4Addr::LOCALHOST, port))
+    }
+
+    pub fn bind<A : ToSocketAddrs>(addr : A) -> io::Result<RemoteServer> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(RemoteServer {
+            listener,
+            clients : vec![],
+            paused  : false,
+            steps   : 0
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Serve pending requests, then update the world, unless paused.
+    pub fn update(&mut self, world : &mut World) {
+        self.poll(world);
+        if self.should_update() {
+            world.update();
+        }
+    }
+
+    /// Like `update`, but with given delta.
+    pub fn update_with_delta(&mut self, world : &mut World, delta : f32) {
+        self.poll(world);
+        if self.should_update() {
+            world.update_with_delta(delta);
+        }
+    }
+
+    /// False while paused, except for requested steps. Counts steps, for custom update loops.
+    pub fn should_update(&mut self) -> bool {
+        if !self.paused {
+            return true;
+        }
+        if self.steps > 0 {
+            self.steps -= 1;
+            return true;
+        }
+        false
+    }
+
+    /// Accept connections and serve all complete requests, without blocking.
+    pub fn poll(&mut self, world : &mut World) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(Client { stream, input : vec![], output : vec![], closed : false });
+            }
+        }
+
+        let mut clients = ::std::mem::take(&mut self.clients);
+        for client in clients.iter_mut() {
+            client.read();
+            while let Some(line) = client.next_line() {
+                let response = self.respond(world, &line);
+                client.output.extend(response.to_string().bytes());
+                client.output.push(b'\n');
+            }
+            client.write();
+        }
+        clients.retain(|client| !client.closed);
+        self.clients = clients;
+    }
+
+    fn respond(&mut self, world : &mut World, line : &str) -> Value {
+        let request = match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(request)) => request,
+            Ok(_) => return error_response(Value::Null, "request should be an object".to_string()),
+            Err(error) => return error_response(Value::Null, format!("invalid json: {}", error))
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        match self.execute(world, &request) {
+            Ok(result) => {
+                let mut response = Map::new();
+                response.insert("id".to_string(), id);
+                response.insert("ok".to_string(), Value::Bool(true));
+                response.insert("result".to_string(), result);
+                Value::Object(response)
+            },
+            Err(error) => error_response(id, error)
+        }
+    }
+
+    fn execute(&mut self, world : &mut World, request : &Map<String, Value>) -> Result<Value, String> {
+        let cmd = request.get("cmd").and_then(Value::as_str).ok_or_else(|| "cmd expected".to_string())?;
+        match cmd {
+            "entities" => Ok(entities(world)),
+            "components" => components(world, entity_arg(request)?),
+            "set" => {
+                let path = request.get("path").and_then(Value::as_str).ok_or_else(|| "path expected".to_string())?;
+                let value = request.get("value").ok_or_else(|| "value expected".to_string())?;
+                set(world, entity_arg(request)?, path, value)
+            },
+            "systems" => Ok(systems(world)),
+            "pause" => {
+                self.paused = true;
+                Ok(Value::Null)
+            },
+            "resume" => {
+                self.paused = false;
+                self.steps = 0;
+                Ok(Value::Null)
+            },
+            "step" => {
+                let frames = request.get("frames").and_then(Value::as_u64).unwrap_or(1);
+                self.paused = true;
+                self.steps = self.steps.saturating_add(u32::try_from(frames).unwrap_or(u32::MAX));
+                Ok(Value::Null)
+            },
+            _ => Err(format!("unknown cmd \"{}\"", cmd))
+        }
+    }
+}
+
+impl Client {
+    fn read(&mut self) {
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.closed = true;
+                    return;
+                },
+                Ok(n) => self.input.extend_from_slice(&buffer[.. n]),
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {},
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => return,
+                Err(_) => {
+                    self.closed = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        loop {
+            let end = self.input.iter().position(|&b| b == b'\n')?;
+            let line = self.input.drain(.. end + 1).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line).trim().to_string();
+            if !line.is_empty() {
+                return Some(line);
+            }
+        }
+    }
+
+    fn write(&mut self) {
+        while !self.output.is_empty() {
+            match self.stream.write(&self.output) {
+                Ok(0) => {
+                    self.closed = true;
+                    return;
+                },
+                Ok(n) => {
+                    self.output.drain(.. n);
+                },
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {},
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => return,
+                Err(_) => {
+                    self.closed = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn error_response(id : Value, error : String) -> Value {
+    let mut response = Map::new();
+    response.insert("id".to_string(), id);
+    response.insert("ok".to_string(), Value::Bool(false));
+    response.insert("error".to_string(), Value::String(error));
+    Value::Object(response)
+}
+
+fn entity_arg(request : &Map<String, Value>) -> Result<i32, String> {
+    let id = request.get("entity").and_then(Value::as_i64).ok_or_else(|| "entity expected".to_string())?;
+    i32::try_from(id).map_err(|_| "entity out of range".to_string())
+}
+
+fn object(fields : Vec<(&str, Value)>) -> Value {
+    Value::Object(fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+}
+
+fn entities(world : &World) -> Value {
+    Value::Array(world.entities.values().map(|entity| {
+        let mut names = entity.components.borrow().keys().map(|type_id| type_name(*type_id)).collect::<Vec<_>>();
+        names.sort();
+        object(vec![
+            ("id", Value::from(entity.id)),
+            ("components", Value::from(names))
+        ])
+    }).collect())
+}
+
+fn components(world : &World, id : i32) -> Result<Value, String> {
+    let entity = world.entities.get(id as usize).ok_or_else(|| format!("no entity {}", id))?;
+    let components = entity.components.borrow();
+    let mut result = components.iter().map(|(type_id, component)| {
+        let registration = world.registry.get(*type_id);
+        let debug = registration.and_then(|r| r.debug).map(|debug| Value::String(debug(&**component)));
+        let fields = registration.and_then(|r| r.reflect)
+            .and_then(|fns| (fns.as_reflect)(&**component))
+            .map(reflect_json);
+        (type_name(*type_id), debug.unwrap_or(Value::Null), fields.unwrap_or(Value::Null))
+    }).collect::<Vec<_>>();
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(Value::Array(result.into_iter().map(|(name, debug, fields)| {
+        object(vec![("name", Value::String(name)), ("debug", debug), ("fields", fields)])
+    }).collect()))
+}
+
+fn reflect_json(reflect : &dyn Reflect) -> Value {
+    match reflect.get_value() {
+        Some(ReflectValue::Int(n)) => Value::from(n),
+        Some(ReflectValue::Float(n)) => Value::from(n),
+        Some(ReflectValue::Bool(b)) => Value::Bool(b),
+        Some(ReflectValue::String(s)) => Value::String(s),
+        None => Value::Object(reflect.field_names().into_iter().map(|name| {
+            (name.to_string(), reflect.field(name).map_or(Value::Null, reflect_json))
+        }).collect())
+    }
+}
+
+fn set(world : &World, id : i32, path : &str, value : &Value) -> Result<Value, String> {
+    let entity = world.entities.get(id as usize).ok_or_else(|| format!("no entity {}", id))?;
+    let value = match *value {
+        Value::Bool(b) => ReflectValue::Bool(b),
+        Value::Number(ref n) if n.is_i64() => ReflectValue::Int(n.as_i64().unwrap()),
+        Value::Number(ref n) => ReflectValue::Float(n.as_f64().unwrap_or(0.0)),
+        Value::String(ref s) => ReflectValue::String(s.clone()),
+        _ => return Err("value should be a number, bool or string".to_string())
+    };
+    entity.set_path(&world.registry, path, value).map_err(|error| error.to_string())?;
+    Ok(Value::Null)
+}
+
+fn micros(duration : Duration) -> Value {
+    Value::from(duration.as_secs() as f64 * 1_000_000.0 + f64::from(duration.subsec_nanos()) / 1000.0)
+}
+
+fn systems(world : &World) -> Value {
+    Value::Array(world.systems.iter().map(|(data, selected)| {
+        let process = data.stats.phase(Phase::Process);
+        object(vec![
+            ("handle", Value::from(data.handle.0)),
+            ("name", Value::String(data.stats.name.clone())),
+            ("group", Value::String(data.group.0.to_string())),
+            ("enabled", Value::Bool(data.enabled)),
+            ("entities", Value::from(selected.entity_set.len())),
+            ("avg_us", micros(process.average())),
+            ("max_us", micros(process.max())),
+            ("p99_us", micros(process.percentile(99.0)))
+        ])
+    }).collect())
+}
@@ -0,0 +1,437 @@
+//! Replication of authoritative world state to clients, enabled by "serde" feature.
+//!
+//! Only components registered with `World::register_replicated` are sent, entities without
+//! them are not replicated at all. Server captures replicated state once per network tick
+//! and sends each client a delta against the last tick, acknowledged by that client:
+//!
+//! ```ignore
+//! // server
+//! world.register_replicated::<Position>("Position");
+//! let client = server.add_client();
+//! server.capture(&world)?;
+//! server.send(client, &mut transport)?;
+//! server.receive_acks(client, &mut transport);
+//!
+//! // client, with the same registrations
+//! replica.receive(&mut client_world, &mut transport)?;
+//! ```
+//!
+//! Only entities, changed since the previous capture, are serialized again, see
+//! `Entity::change_tick`. Components changed since the baseline are found by comparing
+//! their serialized values, so delta size depends only on what really changed.
+//! Client keeps received states, so deltas against any of its recent ticks can be applied,
+//! in order or not.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::fmt;
+use std::error::Error;
+
+use serde_json;
+use bincode;
+
+use entity::Entity;
+use world::World;
+use serialize::BinaryValue;
+
+/// Ticks of state kept by server and client to compute and apply deltas against.
+pub const REPLICATION_HISTORY : usize = 64;
+
+/// Replicated components of one entity by their registered names.
+type EntityState = BTreeMap<String, BinaryValue>;
+/// Replicated entities by their server ids, unchanged entities are shared between ticks.
+type WorldState = BTreeMap<i32, Rc<EntityState>>;
+
+#[derive(Serialize, Deserialize)]
+struct Delta {
+    tick      : u64,
+    /// Tick the delta is computed against, None for full state.
+    baseline  : Option<u64>,
+    spawned   : Vec<i32>,
+    despawned : Vec<i32>,
+    changed   : Vec<(i32, Vec<(String, BinaryValue)>)>,
+    removed   : Vec<(i32, Vec<String>)>
+}
+
+#[derive(Debug)]
+pub enum ReplicationError {
+    Binary(bincode::Error),
+    /// Message mentions component name, not registered for serialization.
+    UnknownComponent(String),
+    /// Registered component failed to serialize or deserialize.
+    Component { name : String, error : serde_json::Error },
+    UnknownClient(ClientId),
+    /// Delta requested before the first `ReplicationServer::capture`.
+    NothingCaptured,
+    /// Delta is computed against a tick, client does not have anymore.
+    UnknownBaseline(u64)
+}
+
+impl fmt::Display for ReplicationError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplicationError::Binary(ref e) => write!(f, "binary format error: {}", e),
+            ReplicationError::UnknownComponent(ref name) =>
+                write!(f, "unknown component type \"{}\"", name),
+            ReplicationError::Component { ref name, ref error } =>
+                write!(f, "component \"{}\": {}", name, error),
+            ReplicationError::UnknownClient(client) => write!(f, "unknown client {:?}", client),
+            ReplicationError::NothingCaptured => write!(f, "no state captured yet"),
+            ReplicationError::UnknownBaseline(tick) => write!(f, "unknown baseline tick {}", tick)
+        }
+    }
+}
+
+impl Error for ReplicationError {}
+
+impl From<bincode::Error> for ReplicationError {
+    fn from(e : bincode::Error) -> ReplicationError {
+        ReplicationError::Binary(e)
+    }
+}
+
+/// Unreliable or reliable channel for replication messages.
+pub trait Transport {
+    fn send(&mut self, message : Vec<u8>);
+    fn receive(&mut self) -> Option<Vec<u8>>;
+}
+
+type Queue = Rc<RefCell<VecDeque<Vec<u8>>>>;
+
+/// In-process transport, for tests and local servers.
+pub struct MemoryTransport {
+    incoming : Queue,
+    outgoing : Queue
+}
+
+impl MemoryTransport {
+    /// Two connected ends, messages sent to one are received by another.
+    pub fn pair() -> (MemoryTransport, MemoryTransport) {
+        let a = Rc::new(RefCell::new(VecDeque::new()));
+        let b = Rc::new(RefCell::new(VecDeque::new()));
+        (MemoryTransport { incoming : a.clone(), outgoing : b.clone() },
+         MemoryTransport { incoming : b, outgoing : a })
+    }
+
+    /// Messages sent to the other end and not received yet.
+    pub fn pending(&self) -> usize {
+        self.outgoing.borrow().len()
+    }
+
+    /// Lose all messages in flight to the other end.
+    pub fn drop_pending(&mut self) {
+        self.outgoing.borrow_mut().clear();
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&mut self, message : Vec<u8>) {
+        self.outgoing.borrow_mut().push_back(message);
+    }
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        self.incoming.borrow_mut().pop_front()
+    }
+}
+
+fn remember(history : &mut VecDeque<(u64, Rc<WorldState>)>, tick : u64, state : Rc<WorldState>) {
+    history.push_back((tick, state));
+    while history.len() > REPLICATION_HISTORY {
+        history.pop_front();
+    }
+}
+
+fn find(history : &VecDeque<(u64, Rc<WorldState>)>, tick : u64) -> Option<&Rc<WorldState>> {
+    history.iter().find(|s| s.0 == tick).map(|s| &s.1)
+}
+
+/// Serialize replicated components of the entity.
+fn capture_entity(world : &World, entity : &Entity) -> Result<EntityState, ReplicationError> {
+    let components = entity.components.borrow();
+    let removed = entity.removed_components.borrow();
+
+    let mut entity_state = EntityState::new();
+    for (type_id, component) in components.iter().filter(|&(t, _)| !removed.contains(t)) {
+        let registration = match world.registry.get(*type_id) {
+            Some(registration) if registration.replicated => registration,
+            _ => continue
+        };
+        let data = (registration.serde.unwrap().serialize)(&**component).map_err(|error| {
+            ReplicationError::Component { name : registration.name.clone(), error }
+        })?;
+        entity_state.insert(registration.name.clone(), BinaryValue::from_json(data));
+    }
+    Ok(entity_state)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ClientId(u32);
+
+/// Authoritative side of replication.
+#[derive(Default)]
+pub struct ReplicationServer {
+    tick        : u64,
+    history     : VecDeque<(u64, Rc<WorldState>)>,
+    /// State of each entity at the last capture, with the entity change tick it was captured at.
+    captured    : HashMap<i32, (u64, Rc<EntityState>)>,
+    /// Number of replicated registrations at the last capture, captured states are dropped when it changes.
+    replicated  : usize,
+    /// Last acknowledged tick of each client.
+    clients     : HashMap<ClientId, Option<u64>>,
+    last_client : u32
+}
+
+impl ReplicationServer {
+    pub fn new() -> ReplicationServer {
+        ReplicationServer::default()
+    }
+
+    /// New client gets full state with the first delta.
+    pub fn add_client(&mut self) -> ClientId {
+        self.last_client += 1;
+        let client = ClientId(self.last_client);
+        self.clients.insert(client, None);
+        client
+    }
+
+    /// Returns false if there is no such client.
+    pub fn remove_client(&mut self, client : ClientId) -> bool {
+        self.clients.remove(&client).is_some()
+    }
+
+    /// Tick of the last capture, 0 before the first one.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Last tick, acknowledged by client.
+    pub fn acked(&self, client : ClientId) -> Option<u64> {
+        self.clients.get(&client).cloned().and_then(|tick| tick)
+    }
+
+    /// Remember replicated state of the world as a new tick.
+    /// Entities, not changed since the previous capture, are not serialized again.
+    pub fn capture(&mut self, world : &World) -> Result<u64, ReplicationError> {
+        let replicated = world.registry.iter().filter(|registration| registration.replicated).count();
+        if replicated != self.replicated {
+            self.captured.clear();
+            self.replicated = replicated;
+        }
+
+        let mut state = WorldState::new();
+        let mut captured = HashMap::with_capacity(self.captured.len());
+        for (id, entity) in world.entities.iter() {
+            let id = id as i32;
+            let change_tick = entity.change_tick();
+            let entity_state = match self.captured.get(&id) {
+                Some(&(tick, ref entity_state)) if tick == change_tick => entity_state.clone(),
+                _ => Rc::new(capture_entity(world, entity)?)
+            };
+            if !entity_state.is_empty() {
+                state.insert(id, entity_state.clone());
+            }
+            captured.insert(id, (change_tick, entity_state));
+        }
+        self.captured = captured;
+
+        self.tick += 1;
+        remember(&mut self.history, self.tick, Rc::new(state));
+        Ok(self.tick)
+    }
+
+    /// Encoded changes of the last captured tick since the tick, acknowledged by client.
+    /// Full state, if client has not acknowledged anything yet, or it is too old.
+    pub fn delta_for(&self, client : ClientId) -> Result<Vec<u8>, ReplicationError> {
+        let acked = *self.clients.get(&client).ok_or(ReplicationError::UnknownClient(client))?;
+        let current = &self.history.back().ok_or(ReplicationError::NothingCaptured)?.1;
+        let empty = WorldState::new();
+        let (baseline, base) = match acked.and_then(|tick| find(&self.history, tick).map(|state| (tick, state))) {
+            Some((tick, state)) => (Some(tick), &**state),
+            None => (None, &empty)
+        };
+
+        let mut delta = Delta {
+            tick      : self.tick,
+            baseline,
+            spawned   : vec![],
+            despawned : base.keys().filter(|id| !current.contains_key(id)).cloned().collect(),
+            changed   : vec![],
+            removed   : vec![]
+        };
+        let no_components = EntityState::new();
+        for (&id, components) in current.iter() {
+            let old = match base.get(&id) {
+                // not changed since the baseline, shared by both states
+                Some(old) if Rc::ptr_eq(old, components) => continue,
+                Some(old) => &**old,
+                None => {
+                    delta.spawned.push(id);
+                    &no_components
+                }
+            };
+            let changed = components.iter()
+                .filter(|&(name, value)| old.get(name) != Some(value))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect::<Vec<_>>();
+            let removed = old.keys().filter(|name| !components.contains_key(*name)).cloned().collect::<Vec<_>>();
+            if !changed.is_empty() {
+                delta.changed.push((id, changed));
+            }
+            if !removed.is_empty() {
+                delta.removed.push((id, removed));
+            }
+        }
+
+        Ok(bincode::serialize(&delta)?)
+    }
+
+    /// Client acknowledged receiving the tick, next deltas are computed against it.
+    pub fn ack(&mut self, client : ClientId, tick : u64) {
+        if let Some(acked) = self.clients.get_mut(&client) {
+            if tick <= self.tick && acked.is_none_or(|acked| tick > acked) {
+                *acked = Some(tick);
+            }
+        }
+    }
+
+    /// Send `delta_for` client.
+    pub fn send(&self, client : ClientId, transport : &mut dyn Transport) -> Result<(), ReplicationError> {
+        transport.send(self.delta_for(client)?);
+        Ok(())
+    }
+
+    /// Read all acknowledgements, sent by `ReplicationClient::receive`. Broken messages are ignored.
+    pub fn receive_acks(&mut self, client : ClientId, transport : &mut dyn Transport) {
+        while let Some(message) = transport.receive() {
+            if let Ok(tick) = bincode::deserialize::<u64>(&message) {
+                self.ack(client, tick);
+            }
+        }
+    }
+}
+
+/// Receiving side of replication, keeps replicated entities of local world in sync with server.
+#[derive(Default)]
+pub struct ReplicationClient {
+    history : VecDeque<(u64, Rc<WorldState>)>,
+    /// Local entity ids by server ids.
+    ids     : HashMap<i32, i32>
+}
+
+impl ReplicationClient {
+    pub fn new() -> ReplicationClient {
+        ReplicationClient::default()
+    }
+
+    /// Last applied server tick.
+    pub fn tick(&self) -> Option<u64> {
+        self.history.back().map(|s| s.0)
+    }
+
+    /// Id of local copy of server entity.
+    pub fn local_id(&self, server_id : i32) -> Option<i32> {
+        self.ids.get(&server_id).cloned()
+    }
+
+    /// Apply delta, made by `ReplicationServer::delta_for`, to the world.
+    /// Returns the tick to acknowledge, or None if the delta is older than the applied state.
+    /// Nothing is changed in the world if applying fails.
+    pub fn apply(&mut self, world : &mut World, message : &[u8]) -> Result<Option<u64>, ReplicationError> {
+        let delta : Delta = bincode::deserialize(message)?;
+        if self.tick().is_some_and(|tick| delta.tick <= tick) {
+            return Ok(None);
+        }
+
+        let mut state = match delta.baseline {
+            Some(tick) => (**find(&self.history, tick).ok_or(ReplicationError::UnknownBaseline(tick))?).clone(),
+            None => WorldState::new()
+        };
+        for id in delta.despawned {
+            state.remove(&id);
+        }
+        for id in delta.spawned {
+            state.insert(id, Rc::new(EntityState::new()));
+        }
+        for (id, components) in delta.changed {
+            Rc::make_mut(state.entry(id).or_default()).extend(components);
+        }
+        for (id, names) in delta.removed {
+            if let Some(components) = state.get_mut(&id) {
+                let components = Rc::make_mut(components);
+                for name in names {
+                    components.remove(&name);
+                }
+            }
+        }
+
+        let empty = WorldState::new();
+        let old = self.history.back().map_or(&empty, |s| &*s.1);
+        let no_components = EntityState::new();
+
+        let mut changes = vec![];
+        for (&id, components) in state.iter() {
+            let old_components = old.get(&id).map_or(&no_components, |old| &**old);
+            let mut added = vec![];
+            for (name, value) in components.iter().filter(|&(name, value)| old_components.get(name) != Some(value)) {
+                let registration = match world.registry.get_by_name(name) {
+                    Some(registration) if registration.serde.is_some() => registration,
+                    _ => return Err(ReplicationError::UnknownComponent(name.clone()))
+                };
+                let component = (registration.serde.unwrap().deserialize)(value.clone().into_json(), registration.storage).map_err(|error| {
+                    ReplicationError::Component { name : name.clone(), error }
+                })?;
+                added.push((registration.type_id, component));
+            }
+            let mut removed = vec![];
+            for name in old_components.keys().filter(|name| !components.contains_key(*name)) {
+                match world.registry.get_by_name(name) {
+                    Some(registration) => removed.push(registration.type_id),
+                    None => return Err(ReplicationError::UnknownComponent(name.clone()))
+                }
+            }
+            changes.push((id, added, removed));
+        }
+
+        for id in old.keys().filter(|id| !state.contains_key(id)) {
+            if let Some(local) = self.ids.remove(id) {
+                world.remove_entity(local);
+            }
+        }
+        for (id, added, removed) in changes {
+            let local = match self.ids.get(&id) {
+                Some(&local) if world.entities.contains_key(local as usize) => local,
+                _ => world.entity_manager().create_entity().id
+            };
+            self.ids.insert(id, local);
+
+            let entity = &world.entities[local as usize];
+            for (type_id, component) in added {
+                entity.removed_components.borrow_mut().remove(&type_id);
+                entity.add_boxed_component(type_id, component);
+            }
+            for type_id in removed {
+                if entity.components.borrow().contains_key(&type_id) {
+                    entity.removed_components.borrow_mut().insert(type_id);
+                    entity.mark_changed();
+                }
+            }
+            entity.refresh();
+        }
+
+        remember(&mut self.history, delta.tick, Rc::new(state));
+        Ok(Some(delta.tick))
+    }
+
+    /// Apply all received deltas and acknowledge them.
+    /// Returns the last applied tick, if any.
+    pub fn receive(&mut self, world : &mut World, transport : &mut dyn Transport) -> Result<Option<u64>, ReplicationError> {
+        let mut applied = None;
+        while let Some(message) = transport.receive() {
+            if let Some(tick) = self.apply(world, &message)? {
+                transport.send(bincode::serialize(&tick)?);
+                applied = Some(tick);
+            }
+        }
+        Ok(applied)
+    }
+}
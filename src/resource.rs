@@ -0,0 +1,61 @@
+use std::collections::{HashMap, HashSet};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+
+use entity::ComponentGuard;
+use type_info::{named_type_id, name_of};
+
+/// World-wide singletons, not attached to any entity.
+///
+/// Resources are stored just like entity components, so several different
+/// resources may be borrowed mutably at the same time.
+pub struct Resources {
+    pub resources : RefCell<HashMap<TypeId, Box<dyn Any>>>,
+    /// Resources, taken out by guards at the moment.
+    pub(crate) borrowed : RefCell<HashSet<TypeId>>
+}
+
+impl Default for Resources {
+    fn default() -> Resources {
+        Resources::new()
+    }
+}
+
+impl Resources {
+    pub fn new() -> Resources {
+        Resources {
+            resources : RefCell::new(HashMap::new()),
+            borrowed  : RefCell::new(HashSet::new())
+        }
+    }
+
+    /// Add resource, replacing the old one of the same type.
+    pub fn insert<T : Any>(&self, resource : T) {
+        self.resources.borrow_mut().insert(named_type_id::<T>(), Box::new(resource));
+    }
+
+    pub fn remove<T : Any>(&self) -> Option<T> {
+        self.resources.borrow_mut().remove(&TypeId::of::<T>()).map(|resource| {
+            *resource.downcast::<T>().unwrap()
+        })
+    }
+
+    pub fn contains<T : Any>(&self) -> bool {
+        self.resources.borrow().contains_key(&TypeId::of::<T>())
+    }
+
+    /// Borrow resource, panics if there is no such resource or it is already borrowed.
+    pub fn get<T : Any>(&self) -> ComponentGuard<'_, T> {
+        match self.try_get::<T>() {
+            Some(resource) => resource,
+            None => panic!("Resource {} is missing or already borrowed", name_of::<T>())
+        }
+    }
+
+    pub fn try_get<T : Any>(&self) -> Option<ComponentGuard<'_, T>> {
+        let resource = self.resources.borrow_mut().remove(&TypeId::of::<T>());
+        resource.map(|resource| {
+            ComponentGuard::new(resource.downcast().unwrap(), &self.resources, &self.borrowed)
+        })
+    }
+}
@@ -0,0 +1,518 @@
+//! Text scenes for authoring levels without recompiling.
+//!
+//! ```text
+//! // prefabs are reusable sets of components
+//! prefab Enemy {
+//!     Health(hp: 100),
+//!     Position(x: 0.0, y: 0.0),
+//! }
+//!
+//! entity player {
+//!     Position(x: 1.0, y: 2.0),
+//!     Name("hero"),
+//!     Player,
+//! }
+//!
+//! // components from prefab may be overriden field by field
+//! entity guard : Enemy {
+//!     parent: player,
+//!     Position(x: 5.0),
+//!     Path(points: [(x: 0, y: 0), (x: 10, y: 0)]),
+//! }
+//! ```
+//!
+//! Component names are names given to `World::register_serde`,
+//! field values are deserialized with the component's serde implementation.
+
+use std::collections::HashMap;
+use std::any::{Any, TypeId};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::fmt;
+use std::error::Error;
+
+use serde_json::{Value, Number, Map};
+
+use world::World;
+use component::Component;
+
+/// Link to the parent entity, added to entities with `parent:` in scene.
+/// Registered in every world as "tinyecs::Parent", so it can be saved, snapshotted and hashed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Parent {
+    pub entity : i32
+}
+impl Component for Parent {}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    /// Malformed scene or scene, not matching registered components.
+    Parse { line : usize, column : usize, message : String }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SceneError::Io(ref e) => write!(f, "io error: {}", e),
+            SceneError::Parse { line, column, ref message } =>
+                write!(f, "{}:{}: {}", line, column, message)
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(e : io::Error) -> SceneError {
+        SceneError::Io(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Location {
+    line   : usize,
+    column : usize
+}
+
+impl Location {
+    fn error<T, S : Into<String>>(self, message : S) -> Result<T, SceneError> {
+        Err(SceneError::Parse { line : self.line, column : self.column, message : message.into() })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(String),
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Colon,
+    Comma,
+    Eof
+}
+
+struct Lexer<'a> {
+    chars    : ::std::iter::Peekable<::std::str::Chars<'a>>,
+    location : Location
+}
+
+impl<'a> Lexer<'a> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.location.line += 1;
+            self.location.column = 1;
+        } else if c.is_some() {
+            self.location.column += 1;
+        }
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some(&c) if c.is_whitespace() => { self.bump(); },
+                Some(&'/') => {
+                    let mut ahead = self.chars.clone();
+                    ahead.next();
+                    if ahead.next() != Some('/') {
+                        return;
+                    }
+                    while self.chars.peek().is_some_and(|&c| c != '\n') {
+                        self.bump();
+                    }
+                },
+                _ => return
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<(Token, Location), SceneError> {
+        self.skip_whitespace();
+        let location = self.location;
+
+        let c = match self.bump() {
+            Some(c) => c,
+            None => return Ok((Token::Eof, location))
+        };
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ':' => Token::Colon,
+            ',' => Token::Comma,
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(c @ '"') | Some(c @ '\\') => s.push(c),
+                            _ => return location.error("invalid escape in string")
+                        },
+                        Some(c) => s.push(c),
+                        None => return location.error("unterminated string")
+                    }
+                }
+                Token::Str(s)
+            },
+            c if c == '-' || c.is_ascii_digit() => {
+                let mut s = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+' || c == '_' {
+                        s.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Number(s)
+            },
+            c if c.is_alphabetic() || c == '_' => {
+                let mut s = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        s.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Ident(s)
+            },
+            c => return location.error(format!("unexpected character '{}'", c))
+        };
+        Ok((token, location))
+    }
+}
+
+struct SceneComponent {
+    name     : String,
+    value    : Value,
+    location : Location
+}
+
+struct SceneEntity {
+    name       : Option<String>,
+    prefab     : Option<(String, Location)>,
+    parent     : Option<(String, Location)>,
+    components : Vec<SceneComponent>
+}
+
+struct Parser<'a> {
+    lexer    : Lexer<'a>,
+    token    : Token,
+    location : Location
+}
+
+impl<'a> Parser<'a> {
+    fn new(source : &'a str) -> Result<Parser<'a>, SceneError> {
+        let mut lexer = Lexer {
+            chars    : source.chars().peekable(),
+            location : Location { line : 1, column : 1 }
+        };
+        let (token, location) = lexer.next_token()?;
+        Ok(Parser { lexer, token, location })
+    }
+
+    fn advance(&mut self) -> Result<Token, SceneError> {
+        let (token, location) = self.lexer.next_token()?;
+        self.location = location;
+        Ok(::std::mem::replace(&mut self.token, token))
+    }
+
+    fn expect(&mut self, token : Token) -> Result<(), SceneError> {
+        if self.token != token {
+            return self.location.error(format!("expected {:?}, found {:?}", token, self.token));
+        }
+        self.advance()?;
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<String, SceneError> {
+        match self.token.clone() {
+            Token::Ident(name) => {
+                self.advance()?;
+                Ok(name)
+            },
+            token => self.location.error(format!("expected name, found {:?}", token))
+        }
+    }
+
+    fn is_field(&self) -> Result<bool, SceneError> {
+        if let Token::Ident(_) = self.token {
+            let mut ahead = Lexer { chars : self.lexer.chars.clone(), location : self.lexer.location };
+            Ok(ahead.next_token()?.0 == Token::Colon)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn parse_scene(&mut self) -> Result<(HashMap<String, SceneEntity>, Vec<SceneEntity>), SceneError> {
+        let mut prefabs = HashMap::new();
+        let mut entities = vec![];
+
+        while self.token != Token::Eof {
+            let location = self.location;
+            let kind = self.ident()?;
+            if kind != "prefab" && kind != "entity" {
+                return location.error(format!("expected \"prefab\" or \"entity\", found \"{}\"", kind));
+            }
+
+            let name = if let Token::Ident(_) = self.token { Some(self.ident()?) } else { None };
+            let prefab = if self.token == Token::Colon {
+                self.advance()?;
+                let location = self.location;
+                Some((self.ident()?, location))
+            } else {
+                None
+            };
+            let entity = self.parse_body(name, prefab)?;
+
+            if let (true, Some(name)) = (kind == "entity", entity.name.as_ref()) {
+                if entities.iter().any(|e : &SceneEntity| e.name.as_ref() == Some(name)) {
+                    return location.error(format!("duplicate entity \"{}\"", name));
+                }
+            }
+            if kind == "prefab" {
+                match entity.name.clone() {
+                    Some(name) => { prefabs.insert(name, entity); },
+                    None => return location.error("prefab must have a name")
+                }
+            } else {
+                entities.push(entity);
+            }
+        }
+        Ok((prefabs, entities))
+    }
+
+    fn parse_body(&mut self, name : Option<String>, prefab : Option<(String, Location)>) -> Result<SceneEntity, SceneError> {
+        let mut entity = SceneEntity { name, prefab, parent : None, components : vec![] };
+
+        self.expect(Token::LBrace)?;
+        while self.token != Token::RBrace {
+            let location = self.location;
+            let name = self.ident()?;
+
+            if name == "parent" && self.token == Token::Colon {
+                self.advance()?;
+                let location = self.location;
+                entity.parent = Some((self.ident()?, location));
+            } else {
+                let value = self.parse_args_or_unit()?;
+                entity.components.push(SceneComponent { name, value, location });
+            }
+
+            if self.token != Token::RBrace {
+                self.expect(Token::Comma)?;
+            }
+        }
+        self.advance()?;
+        Ok(entity)
+    }
+
+    /// `(x: 1, y: 2)` is struct, `(1, 2)` is tuple, `(1)` is newtype and nothing is unit.
+    fn parse_args_or_unit(&mut self) -> Result<Value, SceneError> {
+        if self.token != Token::LParen {
+            return Ok(Value::Null);
+        }
+        self.advance()?;
+
+        let value = if self.is_field()? {
+            let mut fields = Map::new();
+            while self.token != Token::RParen {
+                let location = self.location;
+                let field = self.ident()?;
+                self.expect(Token::Colon)?;
+                if fields.insert(field.clone(), self.parse_value()?).is_some() {
+                    return location.error(format!("duplicate field \"{}\"", field));
+                }
+                if self.token != Token::RParen {
+                    self.expect(Token::Comma)?;
+                }
+            }
+            Value::Object(fields)
+        } else {
+            let mut values = self.parse_list(Token::RParen)?;
+            if values.len() == 1 {
+                values.pop().unwrap()
+            } else {
+                Value::Array(values)
+            }
+        };
+        self.advance()?;
+        Ok(value)
+    }
+
+    fn parse_list(&mut self, end : Token) -> Result<Vec<Value>, SceneError> {
+        let mut values = vec![];
+        while self.token != end {
+            values.push(self.parse_value()?);
+            if self.token != end {
+                self.expect(Token::Comma)?;
+            }
+        }
+        Ok(values)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, SceneError> {
+        let location = self.location;
+        match self.token.clone() {
+            Token::Str(s) => {
+                self.advance()?;
+                Ok(Value::String(s))
+            },
+            Token::Number(n) => {
+                self.advance()?;
+                let number = if let Ok(n) = n.parse::<u64>() {
+                    Some(Number::from(n))
+                } else if let Ok(n) = n.parse::<i64>() {
+                    Some(Number::from(n))
+                } else {
+                    n.parse::<f64>().ok().and_then(Number::from_f64)
+                };
+                match number {
+                    Some(number) => Ok(Value::Number(number)),
+                    None => location.error(format!("invalid number \"{}\"", n))
+                }
+            },
+            Token::LBracket => {
+                self.advance()?;
+                let values = self.parse_list(Token::RBracket)?;
+                self.advance()?;
+                Ok(Value::Array(values))
+            },
+            Token::LParen => self.parse_args_or_unit(),
+            Token::Ident(name) => {
+                self.advance()?;
+                match &name[..] {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    // named struct value, the name is only for readability
+                    _ if self.token == Token::LParen => self.parse_args_or_unit(),
+                    // unit enum variant
+                    _ => Ok(Value::String(name))
+                }
+            },
+            token => location.error(format!("expected value, found {:?}", token))
+        }
+    }
+}
+
+/// Fields from `overrides` replace the same fields in `base`, recursively for structs.
+fn merge(base : &mut Value, overrides : Value) {
+    match (base, overrides) {
+        (&mut Value::Object(ref mut base), Value::Object(overrides)) => {
+            for (field, value) in overrides {
+                if let Some(base) = base.get_mut(&field) {
+                    merge(base, value);
+                } else {
+                    base.insert(field, value);
+                }
+            }
+        },
+        (base, overrides) => *base = overrides
+    }
+}
+
+impl World {
+    /// Load scene file and create its entities.
+    ///
+    /// Returns ids of the named entities. Nothing is created if scene has any errors.
+    pub fn spawn_scene<P : AsRef<Path>>(&mut self, path : P) -> Result<HashMap<String, i32>, SceneError> {
+        let mut source = String::new();
+        File::open(path)?.read_to_string(&mut source)?;
+        self.spawn_scene_str(&source)
+    }
+
+    /// Same as `spawn_scene`, but with scene text instead of file.
+    pub fn spawn_scene_str(&mut self, source : &str) -> Result<HashMap<String, i32>, SceneError> {
+        let (prefabs, entities) = Parser::new(source)?.parse_scene()?;
+
+        let mut built = vec![];
+        for entity in &entities {
+            let mut components : Vec<(String, Value, Location)> = vec![];
+            let mut chain = vec![];
+            let mut current = entity;
+            loop {
+                chain.push(current);
+                match current.prefab {
+                    Some((ref prefab, location)) => match prefabs.get(prefab) {
+                        Some(prefab) if chain.iter().any(|e| ::std::ptr::eq(*e, prefab)) =>
+                            return location.error(format!("prefab \"{}\" includes itself", prefab.name.as_ref().unwrap())),
+                        Some(prefab) => current = prefab,
+                        None => return location.error(format!("unknown prefab \"{}\"", prefab))
+                    },
+                    None => break
+                }
+            }
+            for e in chain.iter().rev() {
+                for c in &e.components {
+                    match components.iter_mut().find(|&&mut (ref name, _, _)| *name == c.name) {
+                        Some(&mut (_, ref mut value, ref mut location)) => {
+                            merge(value, c.value.clone());
+                            *location = c.location;
+                        },
+                        None => components.push((c.name.clone(), c.value.clone(), c.location))
+                    }
+                }
+            }
+
+            let mut boxed : Vec<(TypeId, Box<dyn Any>)> = vec![];
+            for (name, value, location) in components {
+                let registration = match self.registry.get_by_name(&name) {
+                    Some(registration) if registration.serde.is_some() => registration,
+                    _ => return location.error(format!("unknown component \"{}\"", name))
+                };
+                match (registration.serde.unwrap().deserialize)(value, registration.storage) {
+                    Ok(component) => boxed.push((registration.type_id, component)),
+                    Err(e) => return location.error(format!("component \"{}\": {}", name, e))
+                }
+            }
+
+            let parent = chain.iter().filter_map(|e| e.parent.clone()).next();
+            if let Some((ref parent, location)) = parent {
+                if entities.iter().all(|e| e.name.as_ref() != Some(parent)) {
+                    return location.error(format!("unknown parent entity \"{}\"", parent));
+                }
+            }
+            built.push((entity.name.clone(), parent, boxed));
+        }
+
+        let mut ids = HashMap::new();
+        let mut created = vec![];
+        {
+            let mut entity_manager = self.entity_manager();
+            for (name, parent, components) in built {
+                let entity = entity_manager.create_entity();
+                for (type_id, component) in components {
+                    entity.add_boxed_component(type_id, component);
+                }
+                entity.refresh();
+                if let Some(name) = name {
+                    ids.insert(name, entity.id);
+                }
+                created.push((entity.id, parent));
+            }
+            for (id, parent) in created {
+                if let Some((parent, _)) = parent {
+                    let parent = ids[&parent];
+                    entity_manager.try_get_entity(id).unwrap().add_component(Parent { entity : parent });
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
@@ -0,0 +1,347 @@
+//! Saving and loading whole worlds.
+//!
+//! Only components and resources registered with `World::register_serde` can be saved.
+//! Entities get new ids on load, so loaded world can be merged with existing one.
+
+use std::collections::HashMap;
+use std::any::{Any, TypeId};
+use std::io::{self, Read, Write};
+use std::fmt;
+use std::error::Error;
+
+use serde_json::{self, Value, Number, Map};
+use bincode;
+
+use world::World;
+use scene::Parent;
+use clock::Time;
+use registry::ComponentRegistry;
+use type_info::type_name;
+
+/// First bytes of binary save, used to tell it from json on load.
+const BINARY_MAGIC : &[u8] = b"TECS";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable json.
+    Json,
+    /// Compact bincode.
+    Binary
+}
+
+#[derive(Debug)]
+pub enum SerializeError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+    /// Entity has component without serialization registration.
+    UnregisteredComponent { entity : i32, type_id : TypeId },
+    /// World has resource without serialization registration.
+    UnregisteredResource { type_id : TypeId },
+    /// Save file mentions type name, unknown to the registry.
+    UnknownComponent(String),
+    /// Registered component failed to serialize or deserialize.
+    Component { name : String, error : serde_json::Error }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SerializeError::Io(ref e) => write!(f, "io error: {}", e),
+            SerializeError::Json(ref e) => write!(f, "json error: {}", e),
+            SerializeError::Binary(ref e) => write!(f, "binary format error: {}", e),
+            SerializeError::UnregisteredComponent { entity, type_id } =>
+                write!(f, "entity {} has component {}, not registered for serialization", entity, type_name(type_id)),
+            SerializeError::UnregisteredResource { type_id } =>
+                write!(f, "resource {} is not registered for serialization", type_name(type_id)),
+            SerializeError::UnknownComponent(ref name) =>
+                write!(f, "unknown component type \"{}\"", name),
+            SerializeError::Component { ref name, ref error } =>
+                write!(f, "component \"{}\": {}", name, error)
+        }
+    }
+}
+
+impl Error for SerializeError {}
+
+impl From<io::Error> for SerializeError {
+    fn from(e : io::Error) -> SerializeError {
+        SerializeError::Io(e)
+    }
+}
+impl From<serde_json::Error> for SerializeError {
+    fn from(e : serde_json::Error) -> SerializeError {
+        SerializeError::Json(e)
+    }
+}
+impl From<bincode::Error> for SerializeError {
+    fn from(e : bincode::Error) -> SerializeError {
+        SerializeError::Binary(e)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedComponent<V> {
+    name    : String,
+    #[serde(default)]
+    version : u32,
+    data    : V
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedEntity<V> {
+    id         : i32,
+    components : Vec<SavedComponent<V>>
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedWorld<V> {
+    entities  : Vec<SavedEntity<V>>,
+    resources : Vec<SavedComponent<V>>
+}
+
+/// Self describing value for binary saves.
+/// bincode can not deserialize serde_json::Value, so this is a mirror of it.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) enum BinaryValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Array(Vec<BinaryValue>),
+    Object(Vec<(String, BinaryValue)>)
+}
+
+impl BinaryValue {
+    pub(crate) fn from_json(value : Value) -> BinaryValue {
+        match value {
+            Value::Null => BinaryValue::Null,
+            Value::Bool(b) => BinaryValue::Bool(b),
+            Value::Number(n) => {
+                if let Some(n) = n.as_u64() {
+                    BinaryValue::UInt(n)
+                } else if let Some(n) = n.as_i64() {
+                    BinaryValue::Int(n)
+                } else {
+                    BinaryValue::Float(n.as_f64().unwrap())
+                }
+            },
+            Value::String(s) => BinaryValue::String(s),
+            Value::Array(a) => BinaryValue::Array(a.into_iter().map(BinaryValue::from_json).collect()),
+            Value::Object(o) => BinaryValue::Object(o.into_iter().map(|(k, v)| (k, BinaryValue::from_json(v))).collect())
+        }
+    }
+
+    pub(crate) fn into_json(self) -> Value {
+        match self {
+            BinaryValue::Null => Value::Null,
+            BinaryValue::Bool(b) => Value::Bool(b),
+            BinaryValue::Int(n) => Value::Number(n.into()),
+            BinaryValue::UInt(n) => Value::Number(n.into()),
+            BinaryValue::Float(n) => Number::from_f64(n).map_or(Value::Null, Value::Number),
+            BinaryValue::String(s) => Value::String(s),
+            BinaryValue::Array(a) => Value::Array(a.into_iter().map(BinaryValue::into_json).collect()),
+            BinaryValue::Object(o) => Value::Object(o.into_iter().map(|(k, v)| (k, v.into_json())).collect::<Map<_, _>>())
+        }
+    }
+}
+
+impl<V> SavedWorld<V> {
+    fn map<F, U>(self, f : F) -> SavedWorld<U> where F : Fn(V) -> U {
+        let component = |c : SavedComponent<V>| SavedComponent { name : c.name, version : c.version, data : f(c.data) };
+        SavedWorld {
+            entities  : self.entities.into_iter().map(|e| SavedEntity {
+                id         : e.id,
+                components : e.components.into_iter().map(&component).collect()
+            }).collect(),
+            resources : self.resources.into_iter().map(&component).collect()
+        }
+    }
+}
+
+fn save_component(registry : &ComponentRegistry,
+                  type_id : TypeId,
+                  component : &dyn Any,
+                  unregistered : SerializeError) -> Result<SavedComponent<Value>, SerializeError> {
+    let registration = match registry.get(type_id) {
+        Some(registration) if registration.serde.is_some() => registration,
+        _ => return Err(unregistered)
+    };
+    let serde = registration.serde.unwrap();
+    let data = (serde.serialize)(component).map_err(|error| {
+        SerializeError::Component { name : registration.name.clone(), error }
+    })?;
+
+    Ok(SavedComponent { name : registration.name.clone(), version : serde.version, data })
+}
+
+/// Component, that could not be upgraded to the current version.
+#[derive(Debug)]
+pub struct MigrationFailure {
+    /// Id of entity in the save, None for resources.
+    pub entity    : Option<i32>,
+    pub component : String,
+    pub version   : u32,
+    pub error     : String
+}
+
+/// Result of successful `World::load`.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    /// New ids of loaded entities by their ids in the save.
+    pub ids    : HashMap<i32, i32>,
+    /// Entities and resources, skipped because of failed migrations.
+    pub failed : Vec<MigrationFailure>
+}
+
+/// Deserialized component, or reason why it could not be upgraded.
+type LoadedComponent = Result<(TypeId, Box<dyn Any>), MigrationFailure>;
+
+fn load_component(registry : &ComponentRegistry,
+                  entity : Option<i32>,
+                  saved : SavedComponent<Value>) -> Result<LoadedComponent, SerializeError> {
+    let registration = match registry.get_by_name(&saved.name) {
+        Some(registration) if registration.serde.is_some() => registration,
+        _ => return Err(SerializeError::UnknownComponent(saved.name))
+    };
+    let SavedComponent { name, version, data } = saved;
+    let data = match registration.migrate(data, version) {
+        Ok(data) => data,
+        Err(error) => return Ok(Err(MigrationFailure { entity, component : name, version, error }))
+    };
+    let component = (registration.serde.unwrap().deserialize)(data, registration.storage).map_err(|error| {
+        SerializeError::Component { name, error }
+    })?;
+
+    Ok(Ok((registration.type_id, component)))
+}
+
+impl World {
+    /// Write all entities, their components and all resources.
+    ///
+    /// Fails if any of them is not registered with `register_serde`.
+    pub fn save<W : Write>(&self, mut writer : W, format : Format) -> Result<(), SerializeError> {
+        let mut saved = SavedWorld { entities : vec![], resources : vec![] };
+
+        for (id, entity) in self.entities.iter() {
+            let components = entity.components.borrow();
+            let removed = entity.removed_components.borrow();
+
+            let mut saved_components = vec![];
+            for (type_id, component) in components.iter().filter(|&(t, _)| !removed.contains(t)) {
+                let unregistered = SerializeError::UnregisteredComponent { entity : id as i32, type_id : *type_id };
+                saved_components.push(save_component(&self.registry, *type_id, &**component, unregistered)?);
+            }
+            saved_components.sort_by(|a, b| a.name.cmp(&b.name));
+
+            saved.entities.push(SavedEntity { id : id as i32, components : saved_components });
+        }
+
+        for (type_id, resource) in self.resources.resources.borrow().iter() {
+            let unregistered = SerializeError::UnregisteredResource { type_id : *type_id };
+            saved.resources.push(save_component(&self.registry, *type_id, &**resource, unregistered)?);
+        }
+        saved.resources.sort_by(|a, b| a.name.cmp(&b.name));
+
+        match format {
+            Format::Json => serde_json::to_writer_pretty(writer, &saved)?,
+            Format::Binary => {
+                writer.write_all(BINARY_MAGIC)?;
+                bincode::serialize_into(writer, &saved.map(BinaryValue::from_json))?
+            }
+        }
+        Ok(())
+    }
+
+    /// Read world saved with `save`, format is detected automatically.
+    ///
+    /// Loaded entities are added to this world with new ids, mapped from saved ids in the report.
+    /// `Parent` links are remapped to the new ids too.
+    /// Components are kept as `Registration::storage` says.
+    /// Loaded resources replace existing resources of the same type, except `Time`:
+    /// loading a level keeps time scale, pause and frame count of the running world.
+    /// Components saved with older schema versions are upgraded with registered migrations,
+    /// entities with components that could not be upgraded are skipped and listed in the report.
+    /// Nothing is added to world if loading fails.
+    pub fn load<R : Read>(&mut self, reader : R) -> Result<LoadReport, SerializeError> {
+        self.load_impl(reader, false)
+    }
+
+    /// Same as `load`, but saved `Time` replaces the current one too, for restoring saved games.
+    pub fn load_with_time<R : Read>(&mut self, reader : R) -> Result<LoadReport, SerializeError> {
+        self.load_impl(reader, true)
+    }
+
+    fn load_impl<R : Read>(&mut self, mut reader : R, with_time : bool) -> Result<LoadReport, SerializeError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        let saved : SavedWorld<Value> = if bytes.starts_with(BINARY_MAGIC) {
+            let saved : SavedWorld<BinaryValue> = bincode::deserialize(&bytes[BINARY_MAGIC.len()..])?;
+            saved.map(BinaryValue::into_json)
+        } else {
+            serde_json::from_slice(&bytes)?
+        };
+
+        let mut report = LoadReport::default();
+        let mut entities = vec![];
+        for entity in saved.entities {
+            let mut components = vec![];
+            let mut failed = false;
+            for component in entity.components {
+                match load_component(&self.registry, Some(entity.id), component)? {
+                    Ok(component) => components.push(component),
+                    Err(failure) => {
+                        report.failed.push(failure);
+                        failed = true;
+                    }
+                }
+            }
+            if !failed {
+                entities.push((entity.id, components));
+            }
+        }
+        let mut resources = vec![];
+        for resource in saved.resources {
+            match load_component(&self.registry, None, resource)? {
+                Ok(resource) => resources.push(resource),
+                Err(failure) => report.failed.push(failure)
+            }
+        }
+
+        {
+            let mut entity_manager = self.entity_manager();
+            for (old_id, components) in entities {
+                let entity = entity_manager.create_entity();
+                for (type_id, component) in components {
+                    entity.add_boxed_component(type_id, component);
+                }
+                entity.refresh();
+                report.ids.insert(old_id, entity.id);
+            }
+            // parent links point to ids in the save, links to skipped entities are dropped
+            for &id in report.ids.values() {
+                let entity = entity_manager.try_get_entity(id).unwrap();
+                let parent = entity.try_get_component::<Parent>().map(|parent| parent.entity);
+                match parent.map(|parent| report.ids.get(&parent)) {
+                    Some(Some(&parent)) => entity.get_component::<Parent>().entity = parent,
+                    Some(None) => {
+                        entity.remove_component::<Parent>();
+                        entity.refresh();
+                    },
+                    None => {}
+                }
+            }
+        }
+        for (type_id, resource) in resources {
+            if type_id == TypeId::of::<Time>() && !with_time {
+                continue;
+            }
+            self.resources.resources.borrow_mut().insert(type_id, resource);
+        }
+
+        Ok(report)
+    }
+}
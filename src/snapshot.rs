@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+use std::any::{Any, TypeId};
+
+use entity::Entity;
+use registry::{ComponentRegistry, CloneFn};
+use type_info::type_name;
+use world::{World, SelectedEntities};
+
+struct ClonedComponent {
+    type_id   : TypeId,
+    component : Box<dyn Any>,
+    clone     : CloneFn
+}
+
+impl ClonedComponent {
+    fn new(registry : &ComponentRegistry, type_id : TypeId, component : &dyn Any, owner : &str) -> ClonedComponent {
+        let clone = match registry.get(type_id).and_then(|registration| registration.clone) {
+            Some(clone) => clone,
+            None => panic!("{} has component {}, not registered for cloning", owner, type_name(type_id))
+        };
+        ClonedComponent {
+            type_id,
+            component : clone(component),
+            clone
+        }
+    }
+
+    fn duplicate(&self) -> Box<dyn Any> {
+        (self.clone)(&*self.component)
+    }
+}
+
+struct EntitySnapshot {
+    id         : i32,
+    components : Vec<ClonedComponent>,
+    removed    : HashSet<TypeId>,
+    fresh      : bool
+}
+
+/// Copy of the whole world state, made by `World::snapshot`.
+///
+/// Internal state of systems themselves is not captured.
+pub struct WorldSnapshot {
+    entities  : Vec<EntitySnapshot>,
+    resources : Vec<ClonedComponent>,
+    last_id   : i32,
+    selected  : Vec<SelectedEntities>
+}
+
+impl WorldSnapshot {
+    pub fn entities_count(&self) -> usize {
+        self.entities.len()
+    }
+}
+
+impl World {
+    /// Copy all entities, components, resources and systems entity selections.
+    ///
+    /// Panics if any component or resource is not registered with `register_clone`
+    /// or is borrowed at the moment.
+    pub fn snapshot(&self) -> WorldSnapshot {
+        if let Some(type_id) = self.resources.borrowed.borrow().iter().next() {
+            panic!("World has resource {}, borrowed while making snapshot", type_name(*type_id));
+        }
+        let entities = self.entities.iter().map(|(id, entity)| {
+            let owner = format!("Entity {}", id);
+            if let Some(type_id) = entity.borrowed.borrow().iter().next() {
+                panic!("{} has component {}, borrowed while making snapshot", owner, type_name(*type_id));
+            }
+            EntitySnapshot {
+                id         : id as i32,
+                components : entity.components.borrow().iter().map(|(type_id, component)| {
+                    ClonedComponent::new(&self.registry, *type_id, &**component, &owner)
+                }).collect(),
+                removed    : entity.removed_components.borrow().clone(),
+                fresh      : entity.is_fresh()
+            }
+        }).collect();
+
+        let resources = self.resources.resources.borrow().iter().map(|(type_id, resource)| {
+            ClonedComponent::new(&self.registry, *type_id, &**resource, "World")
+        }).collect();
+
+        WorldSnapshot {
+            entities,
+            resources,
+            last_id  : self.last_id,
+            selected : self.systems.iter().map(|(_, selected)| selected.clone()).collect()
+        }
+    }
+
+    /// Bring world back to the state from the snapshot.
+    ///
+    /// Systems entity selections are restored as is, so no on_added or on_removed will be called.
+    /// Snapshot is not consumed and may be restored again.
+    /// Panics if systems were added or removed since the snapshot was made.
+    pub fn restore(&mut self, snapshot : &WorldSnapshot) {
+        assert_eq!(self.systems.len(), snapshot.selected.len(),
+                   "Restoring snapshot, made with different set of systems");
+
+        self.entities.clear();
+        for saved in &snapshot.entities {
+            let entity = Entity::new(saved.id);
+            for component in &saved.components {
+                entity.add_boxed_component(component.type_id, component.duplicate());
+            }
+            *entity.removed_components.borrow_mut() = saved.removed.clone();
+            if saved.fresh {
+                entity.set_fresh();
+            }
+            self.entities.insert(saved.id as usize, entity);
+        }
+
+        let mut resources = self.resources.resources.borrow_mut();
+        resources.clear();
+        for resource in &snapshot.resources {
+            resources.insert(resource.type_id, resource.duplicate());
+        }
+
+        self.last_id = snapshot.last_id;
+        for (&mut (_, ref mut selected), saved) in self.systems.iter_mut().zip(snapshot.selected.iter()) {
+            *selected = saved.clone();
+        }
+    }
+}
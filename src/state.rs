@@ -0,0 +1,227 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+
+use resource::Resources;
+use system::{System, SystemGroup};
+use world::{World, SystemHandle};
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Transition<S> {
+    Set(S),
+    Push(S),
+    Pop
+}
+
+/// Current application state, like menu, loading or gameplay, stored in world resources.
+///
+/// States form a stack, only the top one is current.
+/// Changes are queued and applied by world between frames.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct State<S> {
+    stack  : Vec<S>,
+    queued : Vec<Transition<S>>
+}
+
+impl<S : Clone> Clone for State<S> {
+    fn clone(&self) -> State<S> {
+        State {
+            stack  : self.stack.clone(),
+            queued : self.queued.iter().map(|transition| match *transition {
+                Transition::Set(ref state) => Transition::Set(state.clone()),
+                Transition::Push(ref state) => Transition::Push(state.clone()),
+                Transition::Pop => Transition::Pop
+            }).collect()
+        }
+    }
+}
+
+impl<S> State<S> {
+    pub fn new(initial : S) -> State<S> {
+        State { stack : vec![initial], queued : vec![] }
+    }
+
+    pub fn current(&self) -> &S {
+        self.stack.last().unwrap()
+    }
+
+    /// All states, the current one is the last.
+    pub fn stack(&self) -> &[S] {
+        &self.stack
+    }
+
+    /// Replace the whole stack with given state.
+    pub fn set(&mut self, state : S) {
+        self.queued.push(Transition::Set(state));
+    }
+
+    /// Put state over the current one, like pause menu over gameplay.
+    pub fn push(&mut self, state : S) {
+        self.queued.push(Transition::Push(state));
+    }
+
+    /// Return to the previous state. The last state is never popped.
+    pub fn pop(&mut self) {
+        self.queued.push(Transition::Pop);
+    }
+}
+
+/// When system, added with `World::set_state_system`, is updated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateHook<S> {
+    /// Once, when the state becomes current.
+    Enter(S),
+    /// Once, when the state is left.
+    Exit(S),
+    /// Every frame, while the state is current.
+    Update(S)
+}
+
+/// Type erased part of the world, applying transitions of one `State<S>`.
+pub(crate) trait StateDriver {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Apply queued transitions, returning enter and exit systems to run, in order.
+    fn apply(&mut self, resources : &Resources) -> Vec<SystemHandle>;
+}
+
+struct Driver<S> {
+    on_enter : Vec<(S, SystemHandle)>,
+    on_exit  : Vec<(S, SystemHandle)>
+}
+
+impl<S : PartialEq> Driver<S> {
+    fn hooks(hooks : &[(S, SystemHandle)], state : &S, run : &mut Vec<SystemHandle>) {
+        run.extend(hooks.iter().filter(|hook| hook.0 == *state).map(|hook| hook.1));
+    }
+}
+
+impl<S : 'static + Clone + PartialEq> StateDriver for Driver<S> {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn apply(&mut self, resources : &Resources) -> Vec<SystemHandle> {
+        let mut run = vec![];
+        let mut state = resources.get::<State<S>>();
+        let queued = ::std::mem::take(&mut state.queued);
+
+        for transition in queued {
+            match transition {
+                Transition::Set(new) => {
+                    while let Some(old) = state.stack.pop() {
+                        Self::hooks(&self.on_exit, &old, &mut run);
+                    }
+                    Self::hooks(&self.on_enter, &new, &mut run);
+                    state.stack.push(new);
+                },
+                Transition::Push(new) => {
+                    Self::hooks(&self.on_enter, &new, &mut run);
+                    state.stack.push(new);
+                },
+                Transition::Pop => {
+                    if state.stack.len() > 1 {
+                        let old = state.stack.pop().unwrap();
+                        Self::hooks(&self.on_exit, &old, &mut run);
+                    }
+                }
+            }
+        }
+        run
+    }
+}
+
+impl World {
+    /// Add `State<S>` resource with given initial state.
+    /// World with it can not be saved, use `add_state_serde` for that.
+    /// Panics if state of this type is already added.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// #[derive(Clone, PartialEq, Debug)]
+    /// enum GameState { Menu, Game, Pause }
+    ///
+    /// world.add_state(GameState::Menu);
+    /// world.set_state_system(StateHook::Enter(GameState::Game), SpawnLevelSystem);
+    /// world.set_state_system(StateHook::Update(GameState::Game), MoveSystem);
+    ///
+    /// world.resources().get::<State<GameState>>().set(GameState::Game);
+    /// ```
+    pub fn add_state<S : 'static + Clone + PartialEq + Debug>(&mut self, initial : S) {
+        if self.state_driver::<S>().is_some() {
+            panic!("State {} is already added", ::std::any::type_name::<S>());
+        }
+        self.resources.insert(State::new(initial));
+        self.register_clone::<State<S>>();
+        self.state_drivers.push(Box::new(Driver::<S> { on_enter : vec![], on_exit : vec![] }));
+    }
+
+    /// Like `add_state`, and register `State<S>` for `save` and `load`
+    /// as "tinyecs::State<name>", so the current state is saved with the world.
+    #[cfg(feature = "serde")]
+    pub fn add_state_serde<S>(&mut self, name : &str, initial : S)
+        where S : 'static + Clone + PartialEq + Debug + Serialize + DeserializeOwned {
+        self.add_state(initial);
+        self.register_serde::<State<S>>(&format!("tinyecs::State<{}>", name));
+    }
+
+    fn state_driver<S : 'static>(&mut self) -> Option<&mut Driver<S>> {
+        self.state_drivers.iter_mut().filter_map(|driver| {
+            driver.as_any_mut().downcast_mut::<Driver<S>>()
+        }).next()
+    }
+
+    /// Add system, working only in given state.
+    /// Panics if the state is not added with `add_state`.
+    ///
+    /// Enter and exit systems are updated once, between frames, even if they have no entities -
+    /// to spawn or clean up entities from process_all.
+    pub fn set_state_system<S, TSys>(&mut self, hook : StateHook<S>, system : TSys) -> SystemHandle
+        where S : 'static + Clone + PartialEq + Debug, TSys : 'static + System {
+        if self.state_driver::<S>().is_none() {
+            panic!("State system for state {}, not added with add_state", ::std::any::type_name::<S>());
+        }
+        let handle = match hook {
+            StateHook::Update(_) => self.set_system(system),
+            _ => self.set_system_in_group(SystemGroup::STATE_TRANSITIONS, system)
+        };
+
+        let driver = self.state_driver::<S>().unwrap();
+        match hook {
+            StateHook::Enter(state) => driver.on_enter.push((state, handle)),
+            StateHook::Exit(state) => driver.on_exit.push((state, handle)),
+            StateHook::Update(state) => {
+                self.system_data(handle).in_state = Some(Box::new(move |resources : &Resources| {
+                    *resources.get::<State<S>>().current() == state
+                }));
+            }
+        }
+        handle
+    }
+
+    /// Apply queued state transitions and run their enter and exit systems.
+    pub(crate) fn apply_state_transitions(&mut self) {
+        let start = Instant::now();
+        loop {
+            let mut run = vec![];
+            for driver in self.state_drivers.iter_mut() {
+                run.extend(driver.apply(&self.resources));
+            }
+            if run.is_empty() {
+                break;
+            }
+            for handle in run {
+                self.refresh_entities();
+                self.run_systems(0.0, 0.0, 1.0, |system| system.handle == handle);
+            }
+        }
+        if let Some(ref mut trace) = self.trace {
+            trace.span("state transitions", "world", start, start.elapsed());
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use entity::*;
 use aspect::Aspect;
 use world::{WorldHandle, EntityManager};
+use resource::Resources;
+use clock::Time;
 use std::collections::HashSet;
 
 #[doc(hidden)]
@@ -8,11 +10,7 @@ use std::collections::HashSet;
 macro_rules! impl_aspect {
     ( $( $t:ty ),* ) => {
         fn aspect(&self) -> Aspect {
-            use std::any::TypeId;
-            Aspect {
-                accept_types : vec!($(TypeId::of::<$t>()),*),
-                not_accept_types : Vec::new()
-            }
+            $crate::Aspect::new(vec!($($crate::named_type_id::<$t>()),*), Vec::new())
         }
     }
 }
@@ -40,6 +38,8 @@ macro_rules! impl_new {
 
 /// Create struct and impl System trait for it
 ///
+/// With "derive" feature, `#[system]` attribute also takes optional components, resources and WorldHandle.
+///
 /// ```ignore
 /// register_system!((MoveSystem): |_pos: Position, _vel: Velocity| => {
 /// });
@@ -184,19 +184,26 @@ macro_rules! transit_sync_system {
 /// list with additional entitiy packs from data aspect
 ///
 /// Strongly recommends not use this ever, only for macroses!
+/// Typed `query`, `single` and `optional_single` are the safe way.
 pub struct DataList<'a> {
-    data : Vec<Vec<&'a mut Entity>>
+    pub(crate) data : Vec<Vec<&'a mut Entity>>
 }
 impl<'b> DataList<'b> {
     pub fn unwrap_entity<'a>(&'a self) -> &'a Entity {
-        &self.data[0][0]
+        self.unwrap_entity_nth(0)
     }
 
     pub fn unwrap_entity_nth<'a>(&'a self, n : usize) -> &'a Entity {
-        &self.data[n][0]
+        match self.data.get(n).and_then(|entities| entities.first()) {
+            Some(entity) => entity,
+            None => panic!("Data aspect #{} matched no entities", n)
+        }
     }
     pub fn unwrap_entity_mut<'a>(&'a mut self) -> &'a mut Entity {
-        &mut self.data[0][0]
+        match self.data.get_mut(0).and_then(|entities| entities.first_mut()) {
+            Some(entity) => entity,
+            None => panic!("Data aspect #0 matched no entities")
+        }
     }
 
     pub fn unwrap_all<'a>(&'a mut self) -> &'a mut Vec<&'b mut Entity> {
@@ -211,6 +218,12 @@ impl<'b> DataList<'b> {
     }
 
 
+    pub(crate) fn sort_by_id(&mut self) {
+        for entities in self.data.iter_mut() {
+            entities.sort_by_key(|e| e.id);
+        }
+    }
+
     pub fn new(entity_manager : &mut EntityManager<'b>, ids : &Vec<HashSet<i32>>) -> DataList<'b> {
         DataList {
             data : ids.iter().map(|i| {entity_manager.get_entities_by_ids(&i)}).collect()
@@ -218,6 +231,31 @@ impl<'b> DataList<'b> {
     }
 }
 
+/// Label of a set of systems, updated together.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SystemGroup(pub &'static str);
+
+impl SystemGroup {
+    /// Group of systems added with `World::set_system`.
+    pub const DEFAULT : SystemGroup = SystemGroup("default");
+    /// Systems, updated with constant delta by `World::run_fixed`.
+    pub const FIXED : SystemGroup = SystemGroup("fixed");
+    /// Enter and exit systems of states, updated only on state transitions.
+    pub const STATE_TRANSITIONS : SystemGroup = SystemGroup("state transitions");
+}
+
+/// How often interval system is updated, see `World::set_interval_system`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Interval {
+    Seconds(f32),
+    Frames(u32)
+}
+
+/// Run criteria for systems, updated only once in `n` frames.
+pub fn every_nth_frame(n : u64) -> impl FnMut(&Resources) -> bool {
+    move |resources| resources.get::<Time>().frame_count % n == 0
+}
+
 /// System traits
 ///
 /// You can implement one of those processes, but if you implement process_all - only it will be called, and if you dont implement process_all - all process_* will be called.
@@ -233,17 +271,21 @@ pub trait System {
         Vec::new()
     }
 
-    #[cfg(feature = "prof")]
+    /// Name in `World::system_stats`.
     fn get_name(&self) -> String {
-        use std::intrinsics::*;
-
-        let type_name =
-            unsafe {
-                type_name::<Self>()
-            };
-        type_name.to_string()
+        ::std::any::type_name::<Self>().to_string()
+    }
+    /// Process entities even if the first data aspect matched nothing.
+    /// Systems with typed queries check their cardinality themselves.
+    fn runs_without_data(&self) -> bool {
+        false
+    }
 
+    /// Get real delta in WorldHandle, ignoring `Time::scale`. Useful for UI, working on pause.
+    fn ignores_time_scale(&self) -> bool {
+        false
     }
+
     fn on_created(&mut self, _ : &mut EntityManager) {
 
     }
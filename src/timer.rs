@@ -0,0 +1,75 @@
+use entity::Entity;
+use component::Component;
+use aspect::Aspect;
+use system::System;
+use world::WorldHandle;
+
+/// Countdown, ticked by `TimerSystem`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Timer {
+    pub duration : f32,
+    pub elapsed  : f32,
+    /// Repeating timer starts again after finishing, others are removed from entity.
+    pub repeat   : bool
+}
+impl Component for Timer {}
+
+impl Timer {
+    pub fn once(duration : f32) -> Timer {
+        Timer { duration, elapsed : 0.0, repeat : false }
+    }
+
+    pub fn repeating(duration : f32) -> Timer {
+        Timer { duration, elapsed : 0.0, repeat : true }
+    }
+}
+
+/// Marker, added to entity when its timer finishes.
+/// Systems reacting on timers should remove it after handling.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimerFinished {
+    /// How many times timer finished since the marker was added.
+    pub times : u32
+}
+impl Component for TimerFinished {}
+
+/// Ticks all `Timer` components and marks finished ones with `TimerFinished`.
+pub struct TimerSystem;
+
+impl System for TimerSystem {
+    fn aspect(&self) -> Aspect {
+        Aspect::all::<Timer>()
+    }
+
+    fn process_w(&mut self, entity : &mut Entity, world : &mut WorldHandle) {
+        let mut finished = 0;
+        let mut done = false;
+        {
+            let mut timer = entity.get_component::<Timer>();
+            timer.elapsed += world.delta;
+            while timer.elapsed >= timer.duration && !done {
+                finished += 1;
+                if timer.repeat && timer.duration > 0.0 {
+                    timer.elapsed -= timer.duration;
+                } else {
+                    done = true;
+                }
+            }
+        }
+        if finished == 0 {
+            return;
+        }
+
+        if entity.has_component::<TimerFinished>() {
+            entity.get_component::<TimerFinished>().times += finished;
+        } else {
+            entity.add_component(TimerFinished { times : finished });
+        }
+        if done {
+            entity.remove_component::<Timer>();
+        }
+        entity.refresh();
+    }
+}
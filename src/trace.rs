@@ -0,0 +1,159 @@
+//! Frame timeline recording, exported in Chrome Trace Event format.
+//!
+//! Saved file opens in chrome://tracing or https://ui.perfetto.dev.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Timed part of a frame.
+#[derive(Clone, Debug)]
+pub struct TraceSpan {
+    pub name     : String,
+    /// "world" for refresh and state transitions, phase name for systems.
+    pub category : &'static str,
+    /// Since the trace was enabled.
+    pub start    : Duration,
+    pub duration : Duration
+}
+
+#[derive(Clone, Debug)]
+pub struct TraceFrame {
+    pub number   : u64,
+    pub start    : Duration,
+    pub duration : Duration,
+    pub spans    : Vec<TraceSpan>
+}
+
+/// Ring buffer of last frames timelines, see `World::enable_trace`.
+pub struct Trace {
+    origin     : Instant,
+    capacity   : usize,
+    frames     : VecDeque<TraceFrame>,
+    current    : Option<TraceFrame>,
+    next_frame : u64,
+    spike      : Option<(Duration, PathBuf)>,
+    dump_error : Option<io::Error>
+}
+
+impl Trace {
+    pub fn new(capacity : usize) -> Trace {
+        Trace {
+            origin     : Instant::now(),
+            capacity   : capacity.max(1),
+            frames     : VecDeque::new(),
+            current    : None,
+            next_frame : 0,
+            spike      : None,
+            dump_error : None
+        }
+    }
+
+    /// Finished frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &TraceFrame> {
+        self.frames.iter()
+    }
+
+    /// Save the buffer to `path` each time a frame takes longer than `threshold`.
+    pub fn dump_on_spike<P : AsRef<Path>>(&mut self, threshold : Duration, path : P) {
+        self.spike = Some((threshold, path.as_ref().to_path_buf()));
+    }
+
+    /// Error of the last failed spike dump.
+    pub fn take_dump_error(&mut self) -> Option<io::Error> {
+        self.dump_error.take()
+    }
+
+    pub fn save<P : AsRef<Path>>(&self, path : P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_chrome_trace(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Write frames as Chrome Trace Event JSON.
+    pub fn write_chrome_trace<W : Write>(&self, writer : &mut W) -> io::Result<()> {
+        write!(writer, "{{\"traceEvents\":[")?;
+        let mut first = true;
+        for frame in self.frames.iter() {
+            write_event(writer, &mut first, &format!("frame {}", frame.number), "frame", frame.start, frame.duration)?;
+            for span in frame.spans.iter() {
+                write_event(writer, &mut first, &span.name, span.category, span.start, span.duration)?;
+            }
+        }
+        write!(writer, "],\"displayTimeUnit\":\"ms\"}}")
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        let start = self.origin.elapsed();
+        self.current = Some(TraceFrame {
+            number   : self.next_frame,
+            start,
+            duration : Duration::new(0, 0),
+            spans    : vec![]
+        });
+        self.next_frame += 1;
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        let mut frame = match self.current.take() {
+            Some(frame) => frame,
+            None => return
+        };
+        frame.duration = self.origin.elapsed() - frame.start;
+        let spike = self.spike.as_ref().is_some_and(|&(threshold, _)| frame.duration > threshold);
+
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+
+        if spike {
+            let path = self.spike.as_ref().unwrap().1.clone();
+            if let Err(error) = self.save(path) {
+                self.dump_error = Some(error);
+            }
+        }
+    }
+
+    /// Record span, started at `start`, in the current frame.
+    pub(crate) fn span(&mut self, name : &str, category : &'static str, start : Instant, duration : Duration) {
+        let start = start.duration_since(self.origin);
+        if let Some(ref mut frame) = self.current {
+            frame.spans.push(TraceSpan {
+                name : name.to_string(),
+                category,
+                start,
+                duration
+            });
+        }
+    }
+}
+
+fn write_event<W : Write>(writer : &mut W, first : &mut bool, name : &str, category : &str,
+                          start : Duration, duration : Duration) -> io::Result<()> {
+    if !*first {
+        write!(writer, ",")?;
+    }
+    *first = false;
+    write!(writer, "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":1}}",
+           escape(name), escape(category), micros(start), micros(duration))
+}
+
+fn micros(duration : Duration) -> f64 {
+    duration.as_secs() as f64 * 1_000_000.0 + duration.subsec_nanos() as f64 / 1000.0
+}
+
+fn escape(s : &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c)
+        }
+    }
+    escaped
+}
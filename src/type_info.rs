@@ -0,0 +1,116 @@
+//! Global metadata of component types, to name them in logs and errors instead of TypeId.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::sync::Mutex;
+
+use reflect::{Reflect, FieldInfo};
+
+/// What is known about the type, see `type_info`.
+#[derive(Clone, Debug)]
+pub struct TypeInfo {
+    pub type_id : TypeId,
+    /// Full name with module path, from `std::any::type_name`.
+    pub name    : &'static str,
+    pub size    : usize,
+    pub align   : usize,
+    /// Only for types, registered with `World::register_reflect`.
+    pub fields  : Option<Vec<FieldInfo>>
+}
+
+impl TypeInfo {
+    /// Name without module paths, like `Position` or `Vec<Position>`.
+    pub fn short_name(&self) -> String {
+        short_name(self.name)
+    }
+}
+
+static TYPES : Mutex<Option<HashMap<TypeId, TypeInfo>>> = Mutex::new(None);
+
+fn with_types<R, F : FnOnce(&mut HashMap<TypeId, TypeInfo>) -> R>(f : F) -> R {
+    let mut types = TYPES.lock().unwrap_or_else(|error| error.into_inner());
+    f(types.get_or_insert_with(HashMap::new))
+}
+
+thread_local! {
+    /// Types, already recorded from this thread, to skip locking TYPES on every call.
+    static RECORDED : RefCell<HashSet<TypeId>> = RefCell::new(HashSet::new());
+}
+
+/// TypeId of T, remembering its metadata for `type_info` and `type_name`.
+/// Aspects and `Entity::add_component` use it, so all component types are known.
+/// Metadata is recorded once, later calls only check a thread local set.
+pub fn named_type_id<T : Any>() -> TypeId {
+    let type_id = TypeId::of::<T>();
+    if !RECORDED.with(|recorded| recorded.borrow_mut().insert(type_id)) {
+        return type_id;
+    }
+    with_types(|types| {
+        types.entry(type_id).or_insert_with(|| TypeInfo {
+            type_id,
+            name   : ::std::any::type_name::<T>(),
+            size   : mem::size_of::<T>(),
+            align  : mem::align_of::<T>(),
+            fields : None
+        });
+    });
+    type_id
+}
+
+/// Add field list of T to its metadata, done by `ComponentRegistry::register_reflect`.
+pub(crate) fn record_fields<T : Reflect>() {
+    let type_id = named_type_id::<T>();
+    with_types(|types| types.get_mut(&type_id).unwrap().fields = Some(T::fields()));
+}
+
+pub fn type_info(type_id : TypeId) -> Option<TypeInfo> {
+    with_types(|types| types.get(&type_id).cloned())
+}
+
+/// Type with given full name, or short name, if only one type has it.
+/// Only types, passed to `named_type_id`, are known.
+pub fn type_id_by_name(name : &str) -> Option<TypeId> {
+    with_types(|types| {
+        if let Some(info) = types.values().find(|info| info.name == name) {
+            return Some(info.type_id);
+        }
+        let mut found = types.values().filter(|info| info.short_name() == name);
+        match (found.next(), found.next()) {
+            (Some(info), None) => Some(info.type_id),
+            _ => None
+        }
+    })
+}
+
+/// Short name of type, or TypeId debug output for types never passed to `named_type_id`.
+pub fn type_name(type_id : TypeId) -> String {
+    match type_info(type_id) {
+        Some(info) => info.short_name(),
+        None => format!("{:?}", type_id)
+    }
+}
+
+/// Short name of T, for messages.
+pub(crate) fn name_of<T : ?Sized>() -> String {
+    short_name(::std::any::type_name::<T>())
+}
+
+fn short_name(name : &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment_start = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            short.truncate(segment_start);
+        } else {
+            short.push(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                segment_start = short.len();
+            }
+        }
+    }
+    short
+}
@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use time::PreciseTime;
+use std::time::Instant;
 use vec_map::VecMap;
 
 
@@ -7,34 +7,118 @@ pub use entity::*;
 pub use component::*;
 pub use system::*;
 pub use aspect::*;
+pub use resource::*;
+pub use registry::*;
+pub use clock::*;
+pub use state::*;
+use state::StateDriver;
+use profile::{SystemStats, Phase};
+use trace::Trace;
+use reflect::Reflect;
+
+use std::any::Any;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use scene::Parent;
 
 type EntityIdSet = HashSet<i32>;
 
-struct SystemData {
+/// Decides each frame, should system run or not.
+pub type RunCriteria = Box<dyn FnMut(&Resources) -> bool>;
+
+pub(crate) struct SystemData {
     pub system       : Box<System>,
     pub aspect       : Aspect,
-    pub data_aspects : Vec<Aspect>
+    pub data_aspects : Vec<Aspect>,
+    pub group        : SystemGroup,
+    pub handle       : SystemHandle,
+    pub enabled      : bool,
+    pub run_criteria : Option<RunCriteria>,
+    /// Run criteria of `StateHook::Update` systems.
+    pub in_state     : Option<RunCriteria>,
+    pub interval     : Option<IntervalState>,
+    pub stats        : SystemStats
+}
+
+/// Time and frames, accumulated by interval system since its last run.
+pub(crate) struct IntervalState {
+    pub interval : Interval,
+    pub delta    : f32,
+    pub frames   : u32
 }
 
 impl SystemData {
-    pub fn new(system : Box<System>, aspect : Aspect, data_aspects : Vec<Aspect>) -> SystemData {
+    pub fn new(system : Box<System>, aspect : Aspect, data_aspects : Vec<Aspect>, group : SystemGroup, handle : SystemHandle) -> SystemData {
         SystemData {
+            stats  : SystemStats::new(system.get_name(), handle),
             system : system,
             aspect : aspect,
-            data_aspects : data_aspects
+            data_aspects : data_aspects,
+            group,
+            handle,
+            enabled      : true,
+            run_criteria : None,
+            in_state     : None,
+            interval     : None
+        }
+    }
+
+    /// Delta for this system in current frame, or None if system should not run.
+    fn frame_delta(&mut self, delta : f32, resources : &Resources) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+        let delta = match self.interval {
+            Some(ref mut state) => {
+                state.delta += delta;
+                state.frames += 1;
+                let due = match state.interval {
+                    Interval::Seconds(seconds) => state.delta >= seconds,
+                    Interval::Frames(frames) => state.frames >= frames
+                };
+                if !due {
+                    return None;
+                }
+                state.delta
+            },
+            None => delta
+        };
+        if self.run_criteria.as_mut().is_some_and(|criteria| !criteria(resources)) ||
+            self.in_state.as_mut().is_some_and(|criteria| !criteria(resources)) {
+            return None;
         }
+        if let Some(ref mut state) = self.interval {
+            state.delta = 0.0;
+            state.frames = 0;
+        }
+        Some(delta)
     }
 }
-struct SelectedEntities {
+
+/// Identifies system, added to the world.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SystemHandle(pub(crate) u32);
+#[derive(Clone)]
+pub(crate) struct SelectedEntities {
     pub entity_set    : EntityIdSet,
     pub data_set      : Vec<EntityIdSet>
 }
 
 pub struct World {
-    entities         : VecMap<Entity>,
-    systems          : Vec<(SystemData, SelectedEntities)>,
-    update_time      : PreciseTime,
-    last_id          : i32,
+    pub(crate) entities  : VecMap<Entity>,
+    pub(crate) systems   : Vec<(SystemData, SelectedEntities)>,
+    clock                : Box<dyn Clock>,
+    pub(crate) last_id   : i32,
+    last_system_handle   : u32,
+    deterministic        : bool,
+    fixed_accumulator    : f32,
+    pub(crate) resources : Resources,
+    pub(crate) registry  : ComponentRegistry,
+    pub(crate) state_drivers : Vec<Box<dyn StateDriver>>,
+    pub(crate) trace         : Option<Trace>,
 }
 
 /// part of the world, manipulating entities
@@ -50,7 +134,7 @@ impl<'a> EntityManager<'a> {
 
         let old = self.entities.insert(id as usize, e);
         if let Some(_) = old {
-            panic!("inserting to existing id");
+            panic!("Inserting entity with existing id {}", id);
         }
 
         self.entities.get_mut(*self.last_id as usize).unwrap()
@@ -68,7 +152,10 @@ impl<'a> EntityManager<'a> {
 
     pub fn get_entities_by_ids(&mut self, ids : &HashSet<i32>) -> Vec<&'a mut Entity> {
         ids.iter().map(|id| {
-            let e : &mut Entity = self.entities.get_mut(*id as usize).unwrap();
+            let e : &mut Entity = match self.entities.get_mut(*id as usize) {
+                Some(e) => e,
+                None => panic!("No entity with id {}", id)
+            };
             unsafe {
                 ::std::mem::transmute(e)
             }
@@ -81,17 +168,136 @@ pub struct WorldHandle<'a> {
     /// Delta from last world tick.
     pub delta          : f32,
     /// Entity manager with access to all worlds entities
-    pub entity_manager : EntityManager<'a>
+    pub entity_manager : EntityManager<'a>,
+    /// World-wide resources
+    pub resources      : &'a Resources,
+    /// How far is the current frame between the last and the next fixed step, from 0 to 1.
+    /// Render systems may use it to interpolate states of fixed step systems.
+    /// Always 1 outside of `run_fixed`.
+    pub alpha          : f32
 }
 
 impl World {
     pub fn new() -> World {
-        World {
+        let mut world = World {
             last_id          : 0,
-            update_time      : PreciseTime::now(),
+            last_system_handle : 0,
+            deterministic    : false,
+            fixed_accumulator : 0.0,
+            clock            : Box::new(RealClock::new()),
             entities         : VecMap::with_capacity(3000),
-            systems          : Vec::new()
+            systems          : Vec::new(),
+            resources        : Resources::new(),
+            registry         : ComponentRegistry::new(),
+            state_drivers    : Vec::new(),
+            trace            : None
+        };
+        world.resources.insert(Time::default());
+        world.register_clone::<Time>();
+        #[cfg(feature = "serde")]
+        world.register_serde::<Time>("tinyecs::Time");
+        #[cfg(feature = "serde")]
+        {
+            world.register_serde::<Parent>("tinyecs::Parent");
+            world.register_clone::<Parent>();
+            world.register_hash::<Parent>();
         }
+        world
+    }
+
+    /// Replace the clock, measuring frame deltas for `update` and `run_fixed`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let clock = ManualClock::new();
+    /// world.set_clock(clock.clone());
+    ///
+    /// clock.advance(0.5);
+    /// world.update(); // delta is 0.5
+    /// ```
+    pub fn set_clock<C : Clock + 'static>(&mut self, clock : C) {
+        self.clock = Box::new(clock);
+    }
+
+    /// World-wide resources, also accessible from systems through WorldHandle.
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn registry(&self) -> &ComponentRegistry {
+        &self.registry
+    }
+
+    pub fn registry_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.registry
+    }
+
+    /// Register component type as its `Component::register` says,
+    /// for types with `#[derive(Component)]` - as its attributes say.
+    pub fn register_component<T : Component>(&mut self) {
+        T::register(&mut self.registry);
+    }
+
+    /// Register component type for creating by name, see `ComponentRegistry::register_default`.
+    pub fn register_default<T : Any + Default>(&mut self) {
+        self.registry.register_default::<T>();
+    }
+
+    /// Register component type for reflected access, see `ComponentRegistry::register_reflect`.
+    pub fn register_reflect<T : Reflect>(&mut self) {
+        self.registry.register_reflect::<T>();
+    }
+
+    /// Register component or resource type for `snapshot` and `restore`.
+    pub fn register_clone<T : Any + Clone>(&mut self) {
+        self.registry.register_clone::<T>();
+    }
+
+    /// Register component or resource type for `save` and `load` under given stable name.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Position { x : f32, y : f32 }
+    /// impl Component for Position {}
+    ///
+    /// world.register_serde::<Position>("Position");
+    /// world.save(File::create("level.json").unwrap(), Format::Json).unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn register_serde<T : Any + Serialize + DeserializeOwned>(&mut self, name : &str) {
+        self.registry.register_serde::<T>(name, 0);
+    }
+
+    /// Same as `register_serde`, but with schema version, written to saves.
+    /// Older saved data is upgraded with migrations on load.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// // version 0 was Position { x : f32 }
+    /// world.register_serde_versioned::<Position>("Position", 1);
+    /// world.register_migration("Position", 0, |mut data| {
+    ///     data["y"] = 0.0.into();
+    ///     Ok(data)
+    /// });
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn register_serde_versioned<T : Any + Serialize + DeserializeOwned>(&mut self, name : &str, version : u32) {
+        self.registry.register_serde::<T>(name, version);
+    }
+
+    /// Register component type for `ReplicationServer` under given stable name.
+    /// It is also registered for `save` and `load`, like with `register_serde`.
+    #[cfg(feature = "serde")]
+    pub fn register_replicated<T : Any + Serialize + DeserializeOwned>(&mut self, name : &str) {
+        self.registry.register_replicated::<T>(name);
+    }
+
+    /// Register upgrade of component with given name from `from` version to `from + 1`.
+    #[cfg(feature = "serde")]
+    pub fn register_migration<F>(&mut self, name : &str, from : u32, migration : F)
+        where F : Fn(::serde_json::Value) -> Result<::serde_json::Value, String> + 'static {
+        self.registry.register_migration(name, from, migration);
     }
 
     /// Get entity manager for manupalating with entities.
@@ -115,7 +321,15 @@ impl World {
     }
 
     /// Add new active system.
-    pub fn set_system<TSys>(&mut self, mut system : TSys)
+    pub fn set_system<TSys>(&mut self, system : TSys) -> SystemHandle
+        where TSys : 'static + System {
+        self.set_system_in_group(SystemGroup::DEFAULT, system)
+    }
+
+    /// Add new active system to the given group.
+    ///
+    /// Systems in `SystemGroup::FIXED` are updated with constant delta by `run_fixed`.
+    pub fn set_system_in_group<TSys>(&mut self, group : SystemGroup, mut system : TSys) -> SystemHandle
         where TSys : 'static + System {
         let aspect = system.aspect();
         let data_aspects = system.data_aspects();
@@ -124,7 +338,10 @@ impl World {
             last_id          : &mut self.last_id,
             entities         : &mut self.entities
         });
-        self.systems.push((SystemData::new(Box::new(system), aspect, data_aspects),
+        self.last_system_handle += 1;
+        let handle = SystemHandle(self.last_system_handle);
+
+        self.systems.push((SystemData::new(Box::new(system), aspect, data_aspects, group, handle),
                                         SelectedEntities {
                                             entity_set : HashSet::new(),
                                             data_set   : vec![HashSet::new(); 0]
@@ -132,58 +349,290 @@ impl World {
         for (_, e) in self.entities.iter_mut() {
             e.set_fresh();
         }
+        handle
+    }
+
+    /// Add system, updated only once in a given interval, instead of every frame.
+    /// Its WorldHandle::delta is the time, accumulated since the last run.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// world.set_interval_system(Interval::Seconds(0.2), AiPlannerSystem);
+    /// world.set_interval_system(Interval::Frames(10), MinimapSystem);
+    /// ```
+    pub fn set_interval_system<TSys>(&mut self, interval : Interval, system : TSys) -> SystemHandle
+        where TSys : 'static + System {
+        let handle = self.set_system(system);
+        self.system_data(handle).interval = Some(IntervalState { interval, delta : 0.0, frames : 0 });
+        handle
+    }
+
+    /// Disabled system is not updated, but still tracks its entities,
+    /// so no on_added or on_removed is fired when it gets enabled back.
+    pub fn set_enabled(&mut self, handle : SystemHandle, enabled : bool) {
+        self.system_data(handle).enabled = enabled;
+    }
+
+    pub fn is_enabled(&self, handle : SystemHandle) -> bool {
+        self.systems.iter().find(|s| s.0.handle == handle).is_some_and(|s| s.0.enabled)
+    }
+
+    /// System will be updated only in frames, when given closure returns true.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let handle = world.set_system(AiSystem);
+    /// world.set_run_criteria(handle, |resources| resources.get::<GameState>().paused == false);
+    /// world.set_run_criteria(physics, every_nth_frame(2));
+    /// ```
+    pub fn set_run_criteria<F>(&mut self, handle : SystemHandle, criteria : F)
+        where F : FnMut(&Resources) -> bool + 'static {
+        self.system_data(handle).run_criteria = Some(Box::new(criteria));
+    }
+
+    pub fn clear_run_criteria(&mut self, handle : SystemHandle) {
+        self.system_data(handle).run_criteria = None;
+    }
+
+    /// Remove entity from the world, firing on_removed of its systems.
+    /// Returns false if there is no such entity.
+    pub fn remove_entity(&mut self, id : i32) -> bool {
+        let mut entity = match self.entities.remove(id as usize) {
+            Some(entity) => entity,
+            None => return false
+        };
+        for &mut (ref mut data, ref mut selected) in self.systems.iter_mut() {
+            if selected.entity_set.remove(&id) {
+                let SystemData { ref mut system, ref mut stats, .. } = *data;
+                stats.time(Phase::Removed, None, || system.on_removed(&mut entity));
+            }
+            for set in selected.data_set.iter_mut() {
+                set.remove(&id);
+            }
+        }
+        true
+    }
+
+    /// Remove system from the world, firing on_removed for all its entities.
+    /// Returns false if there is no such system.
+    pub fn remove_system(&mut self, handle : SystemHandle) -> bool {
+        let index = match self.systems.iter().position(|s| s.0.handle == handle) {
+            Some(index) => index,
+            None => return false
+        };
+        let (data, selected) = self.systems.remove(index);
+
+        let mut ids = selected.entity_set.into_iter().collect::<Vec<_>>();
+        ids.sort();
+        for id in ids {
+            if let Some(entity) = self.entities.get_mut(id as usize) {
+                data.system.on_removed(entity);
+            }
+        }
+        true
+    }
+
+    pub(crate) fn system_data(&mut self, handle : SystemHandle) -> &mut SystemData {
+        match self.systems.iter_mut().find(|s| s.0.handle == handle) {
+            Some(system) => &mut system.0,
+            None => panic!("No system with handle {:?}", handle)
+        }
+    }
+
+    /// Timings of all systems, in update order.
+    pub fn system_stats(&self) -> Vec<&SystemStats> {
+        self.systems.iter().map(|s| &s.0.stats).collect()
+    }
+
+    /// Process entities in stable id order instead of hash order,
+    /// so two runs with the same input give the same results.
+    /// Use with `update_with_delta` for lockstep simulation and replays.
+    pub fn set_deterministic(&mut self, deterministic : bool) {
+        self.deterministic = deterministic;
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
     }
 
     /// Tick all systems in world.
     /// All on_added and on_removed will passed inside this method.
     pub fn update(&mut self) {
-        let delta = self.update_time.to(PreciseTime::now());
-        let float_delta = delta.num_seconds() as f32 + delta.num_milliseconds() as f32 / 1000.0;
+        let delta = self.clock.tick();
+        self.update_with_delta(delta);
+    }
 
-        self.update_time = PreciseTime::now();
+    /// Tick all systems in world with given delta instead of the clock's one.
+    pub fn update_with_delta(&mut self, delta : f32) {
+        self.begin_trace_frame();
+        let scaled_delta = self.advance_time(delta);
 
-        let mut systems = &mut self.systems;
+        self.apply_state_transitions();
+        self.refresh_entities();
+        self.run_systems(delta, scaled_delta, 1.0, |system| system.group != SystemGroup::STATE_TRANSITIONS);
+        self.end_trace_frame();
+    }
 
-        {
-            profile_region!("refresh entities");
-            for (_, e) in self.entities.iter_mut() {
-                //.filter(|&(_, ref e)| {*e.fresh.borrow_mut() == false})
-                Self::refresh_entity(e, systems);
-                e.set_fresh();
-            }
+    /// Fixed timestep update.
+    ///
+    /// Scaled time is accumulated, and systems from `SystemGroup::FIXED` are updated
+    /// with `step` delta as many times as it fits into accumulated time, but not more than `max_substeps`.
+    /// All other systems are updated once, with frame delta and `WorldHandle::alpha` - part of step
+    /// left in accumulator.
+    ///
+    /// Returns number of fixed steps made. Panics if step is not positive.
+    pub fn run_fixed(&mut self, step : f32, max_substeps : u32) -> u32 {
+        let delta = self.clock.tick();
+        self.run_fixed_with_delta(step, max_substeps, delta)
+    }
+
+    /// Same as `run_fixed`, but with given delta instead of the clock's one.
+    pub fn run_fixed_with_delta(&mut self, step : f32, max_substeps : u32, delta : f32) -> u32 {
+        assert!(step > 0.0, "Fixed step should be positive, got {}", step);
+        self.begin_trace_frame();
+        let scaled_delta = self.advance_time(delta);
+        self.fixed_accumulator += scaled_delta;
+
+        self.apply_state_transitions();
+
+        let mut substeps = 0;
+        while self.fixed_accumulator >= step && substeps < max_substeps {
+            self.refresh_entities();
+            self.run_systems(step, step, 1.0, |system| system.group == SystemGroup::FIXED);
+            self.fixed_accumulator -= step;
+            substeps += 1;
+        }
+        // do not try to catch up after a long frame spike, just drop that time
+        if self.fixed_accumulator >= step {
+            self.fixed_accumulator %= step;
+        }
+
+        let alpha = self.fixed_accumulator / step;
+        self.refresh_entities();
+        self.run_systems(delta, scaled_delta, alpha, |system| {
+            system.group != SystemGroup::FIXED && system.group != SystemGroup::STATE_TRANSITIONS
+        });
+        self.end_trace_frame();
+
+        substeps
+    }
+
+    /// Tick only systems from given group, with given delta.
+    /// Does not count as a frame in `Time`.
+    pub fn update_group(&mut self, group : SystemGroup, delta : f32) {
+        let scaled_delta = delta * self.resources.get::<Time>().scale;
+
+        self.begin_trace_frame();
+        self.refresh_entities();
+        self.run_systems(delta, scaled_delta, 1.0, |system| system.group == group);
+        self.end_trace_frame();
+    }
+
+    /// Start recording timeline of the last `frames` frames, see `trace`.
+    pub fn enable_trace(&mut self, frames : usize) {
+        self.trace = Some(Trace::new(frames));
+    }
+
+    /// Stop recording, returns recorded trace.
+    pub fn disable_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    pub fn trace_mut(&mut self) -> Option<&mut Trace> {
+        self.trace.as_mut()
+    }
+
+    fn begin_trace_frame(&mut self) {
+        if let Some(ref mut trace) = self.trace {
+            trace.begin_frame();
         }
+    }
+
+    fn end_trace_frame(&mut self) {
+        if let Some(ref mut trace) = self.trace {
+            trace.end_frame();
+        }
+    }
+
+    /// Update `Time` resource for the new frame, returns scaled delta.
+    fn advance_time(&mut self, delta : f32) -> f32 {
+        if !self.resources.contains::<Time>() {
+            self.resources.insert(Time::default());
+        }
+        let mut time = self.resources.get::<Time>();
+        time.advance(delta);
+        time.delta
+    }
+
+    pub(crate) fn refresh_entities(&mut self) {
+        let start = Instant::now();
+        for (_, e) in self.entities.iter_mut() {
+            //.filter(|&(_, ref e)| {*e.fresh.borrow_mut() == false})
+            Self::refresh_entity(e, &mut self.systems);
+            e.set_fresh();
+        }
+        if let Some(ref mut trace) = self.trace {
+            trace.span("refresh", "world", start, start.elapsed());
+        }
+    }
+
+    pub(crate) fn run_systems<F>(&mut self, delta : f32, scaled_delta : f32, alpha : f32, filter : F)
+        where F : Fn(&SystemData) -> bool {
+        let deterministic = self.deterministic;
+        let systems = &mut self.systems;
+        let mut trace = self.trace.as_mut();
+
+        let resources = &self.resources;
+        let active = systems.iter_mut().map(|s| {
+            if !filter(&s.0) {
+                return None;
+            }
+            let delta = if s.0.system.ignores_time_scale() { delta } else { scaled_delta };
+            s.0.frame_delta(delta, resources)
+        }).collect::<Vec<_>>();
 
         let mut world_data = WorldHandle {
-            delta    : float_delta,
+            delta            : scaled_delta,
             entity_manager   : EntityManager {
                 last_id      : &mut self.last_id,
                 entities     : &mut self.entities
-            }
+            },
+            resources        : &self.resources,
+            alpha
         };
 
 
         {
-            profile_region!("all begin frames");
-            for &mut (ref mut system, ref entities) in systems.iter_mut() {
-                if entities.entity_set.len() != 0 {
-                    profile_region!(&format!("on_begin_frame: {}", system.system.get_name()));
-                    (*system.system).on_begin_frame();
+            for &mut (ref mut system, ref entities) in systems.iter_mut().zip(&active).filter(|s| s.1.is_some()).map(|s| s.0) {
+                if !entities.entity_set.is_empty() || system.group == SystemGroup::STATE_TRANSITIONS {
+                    let SystemData { system : ref mut s, ref mut stats, .. } = *system;
+                    stats.time(Phase::BeginFrame, trace.as_deref_mut(), || s.on_begin_frame());
                 }
             }
         }
         {
-            profile_region!("all updates");
-            for &mut (ref mut system, ref mut entities) in systems.iter_mut() {
-                if entities.entity_set.len() != 0 {
+            for (&mut (ref mut system, ref mut entities), delta) in systems.iter_mut().zip(&active).filter_map(|(s, d)| d.map(|d| (s, d))) {
+                if !entities.entity_set.is_empty() || system.group == SystemGroup::STATE_TRANSITIONS {
                     let mut refs = world_data.entity_manager.get_entities_by_ids(&entities.entity_set);
+                    if deterministic {
+                        refs.sort_by_key(|e| e.id);
+                    }
 
                     {
-                        profile_region!(&system.system.get_name());
-                        if system.data_aspects.len() == 0 ||
+                        if system.data_aspects.len() == 0 || system.system.runs_without_data() ||
                             (entities.data_set.len() != 0 &&
                              entities.data_set[0].len() != 0) {
                             let mut some_data = DataList::new(&mut world_data.entity_manager, &entities.data_set);
-                            (*system.system).process_all(&mut refs, &mut world_data, &mut some_data);
+                            if deterministic {
+                                some_data.sort_by_id();
+                            }
+                            world_data.delta = delta;
+                            let SystemData { system : ref mut s, ref mut stats, .. } = *system;
+                            stats.time(Phase::Process, trace.as_deref_mut(), || s.process_all(&mut refs, &mut world_data, &mut some_data));
                         }
                     }
                 }
@@ -191,11 +640,10 @@ impl World {
         }
 
         {
-            profile_region!("all end frames");
-            for &mut(ref mut system, ref entities) in systems.iter_mut() {
-                if entities.entity_set.len() != 0 {
-                    profile_region!(&format!("end_frame: {}", system.system.get_name()));
-                    (*system.system).on_end_frame();
+            for &mut(ref mut system, ref entities) in systems.iter_mut().zip(&active).filter(|s| s.1.is_some()).map(|s| s.0) {
+                if !entities.entity_set.is_empty() || system.group == SystemGroup::STATE_TRANSITIONS {
+                    let SystemData { system : ref mut s, ref mut stats, .. } = *system;
+                    stats.time(Phase::EndFrame, trace.as_deref_mut(), || s.on_end_frame());
                 }
             }
         }
@@ -213,19 +661,16 @@ impl World {
             }
         }
 
-        for & mut(SystemData { ref mut system, ref mut aspect, ref mut data_aspects }, ref mut entities) in systems.iter_mut() {
+        for & mut(SystemData { ref mut system, ref mut aspect, ref mut data_aspects, ref mut stats, .. }, ref mut entities) in systems.iter_mut() {
             if aspect.check(e) {
                 if entities.entity_set.contains(&e.id) == false {
-                    profile_region!(&format!("on_added: {}", system.get_name()));
-
                     entities.entity_set.insert(e.id);
-                    system.on_added(e);
+                    stats.time(Phase::Added, None, || system.on_added(e));
                 }
             } else {
                 if entities.entity_set.contains(&e.id) {
-                    profile_region!(&format!("on_removed: {}", system.get_name()));
                     entities.entity_set.remove(&e.id);
-                    system.on_removed(e);
+                    stats.time(Phase::Removed, None, || system.on_removed(e));
                 }
             }
 
@@ -0,0 +1,85 @@
+extern crate tinyecs;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use tinyecs::*;
+
+pub struct Body;
+impl Component for Body {}
+
+pub struct DeltaSystem {
+    unscaled : bool,
+    log      : DeltaLog
+}
+impl System for DeltaSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Body)
+    }
+    fn ignores_time_scale(&self) -> bool {
+        self.unscaled
+    }
+    fn process_w(&mut self, _ : &mut Entity, world : &mut WorldHandle) {
+        self.log.borrow_mut().push(world.delta);
+    }
+}
+
+type DeltaLog = Rc<RefCell<Vec<f32>>>;
+
+fn make_world() -> (World, DeltaLog, DeltaLog) {
+    let game = Rc::new(RefCell::new(vec![]));
+    let ui = Rc::new(RefCell::new(vec![]));
+
+    let mut world = World::new();
+    world.set_system(DeltaSystem { unscaled : false, log : game.clone() });
+    world.set_system(DeltaSystem { unscaled : true, log : ui.clone() });
+    world.entity_manager().create_entity().add_component(Body);
+
+    (world, game, ui)
+}
+
+#[test]
+fn test_manual_clock_and_scale() {
+    let (mut world, game, ui) = make_world();
+    let clock = ManualClock::new();
+    world.set_clock(clock.clone());
+
+    clock.advance(0.5);
+    world.update();
+    world.resources().get::<Time>().scale = 0.5;
+    clock.advance(1.0);
+    world.update();
+    world.resources().get::<Time>().scale = 0.0;
+    clock.advance(1.0);
+    world.update();
+
+    assert_eq!(*game.borrow(), vec![0.5, 0.5, 0.0]);
+    assert_eq!(*ui.borrow(), vec![0.5, 1.0, 1.0]);
+
+    let time = world.resources().get::<Time>();
+    assert_eq!(time.frame_count, 3);
+    assert_eq!(time.elapsed, 1.0);
+    assert_eq!(time.raw_elapsed, 2.5);
+    assert_eq!(time.raw_delta, 1.0);
+    assert_eq!(time.delta, 0.0);
+}
+
+#[test]
+fn test_recorded_clock() {
+    let (mut world, game, _) = make_world();
+    let source = ManualClock::new();
+    let recorder = RecordedClock::record(source.clone());
+    world.set_clock(recorder.clone());
+
+    for &delta in &[0.1, 0.25, 0.03] {
+        source.advance(delta);
+        world.update();
+    }
+    assert_eq!(recorder.deltas(), vec![0.1, 0.25, 0.03]);
+
+    let (mut replay, replayed, _) = make_world();
+    replay.set_clock(RecordedClock::playback(recorder.deltas()));
+    for _ in 0 .. 3 {
+        replay.update();
+    }
+    assert_eq!(*replayed.borrow(), *game.borrow());
+}
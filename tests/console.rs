@@ -0,0 +1,143 @@
+extern crate tinyecs;
+
+use std::any::Any;
+use tinyecs::*;
+
+#[derive(Default)]
+pub struct Health {
+    pub hp : i32
+}
+impl Component for Health {}
+
+impl Reflect for Health {
+    fn fields() -> Vec<FieldInfo> {
+        vec![FieldInfo { name : "hp", type_name : "i32" }]
+    }
+    fn field_names(&self) -> Vec<&'static str> {
+        vec!["hp"]
+    }
+    fn field(&self, name : &str) -> Option<&dyn Reflect> {
+        if name == "hp" { Some(&self.hp) } else { None }
+    }
+    fn field_mut(&mut self, name : &str) -> Option<&mut dyn Reflect> {
+        if name == "hp" { Some(&mut self.hp) } else { None }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct Name {
+    pub s : String
+}
+impl Component for Name {}
+
+impl Reflect for Name {
+    fn fields() -> Vec<FieldInfo> {
+        vec![FieldInfo { name : "s", type_name : "String" }]
+    }
+    fn field_names(&self) -> Vec<&'static str> {
+        vec!["s"]
+    }
+    fn field(&self, name : &str) -> Option<&dyn Reflect> {
+        if name == "s" { Some(&self.s) } else { None }
+    }
+    fn field_mut(&mut self, name : &str) -> Option<&mut dyn Reflect> {
+        if name == "s" { Some(&mut self.s) } else { None }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct Poisoned;
+impl Component for Poisoned {}
+
+pub struct PoisonSystem;
+impl System for PoisonSystem {
+    fn aspect(&self) -> Aspect {
+        Aspect::all2::<Health, Poisoned>()
+    }
+    fn process_one(&mut self, entity : &mut Entity) {
+        entity.get_component::<Health>().hp -= 1;
+    }
+}
+
+fn make_world() -> World {
+    let mut world = World::new();
+    world.registry_mut().register(::std::any::TypeId::of::<Health>(), "Health");
+    world.register_default::<Health>();
+    world.register_default::<Poisoned>();
+    world.register_reflect::<Health>();
+    world.register_default::<Name>();
+    world.register_reflect::<Name>();
+    world.set_system(PoisonSystem);
+    world
+}
+
+#[test]
+fn test_console_commands() {
+    let mut world = make_world();
+    let mut console = Console::new();
+
+    assert_eq!(console.execute(&mut world, "spawn Health Poisoned").unwrap(), "spawned 1");
+    assert_eq!(console.execute(&mut world, "spawn Health").unwrap(), "spawned 2");
+    assert_eq!(console.execute(&mut world, "set 1 Health.hp 10").unwrap(), "Health.hp = 10");
+    world.update_with_delta(0.0);
+    assert_eq!(console.execute(&mut world, "get 1 Health.hp").unwrap(), "9");
+
+    assert_eq!(console.execute(&mut world, "list Poisoned").unwrap(), "1: Health, Poisoned");
+    assert_eq!(console.execute(&mut world, "systems").unwrap(), "1: PoisonSystem [default] enabled, 1 entities");
+    assert!(console.execute(&mut world, "inspect 1").unwrap().contains("+ "));
+
+    console.execute(&mut world, "disable PoisonSystem").unwrap();
+    world.update_with_delta(0.0);
+    assert_eq!(console.execute(&mut world, "get 1 Health.hp").unwrap(), "9");
+    console.execute(&mut world, "enable 1").unwrap();
+
+    assert_eq!(console.execute(&mut world, "destroy 1").unwrap(), "destroyed 1");
+    assert_eq!(console.execute(&mut world, "list").unwrap(), "2: Health");
+}
+
+#[test]
+fn test_console_errors() {
+    let mut world = make_world();
+    let mut console = Console::new();
+    console.register("echo", "echo <text>", |_, args| Ok(args.join(" ")));
+
+    assert_eq!(console.execute(&mut world, "echo \"a  b\" c").unwrap(), "a  b c");
+    assert_eq!(console.execute(&mut world, "  "), Err(ConsoleError::Empty));
+    assert_eq!(console.execute(&mut world, "fly"), Err(ConsoleError::UnknownCommand("fly".to_string())));
+    assert_eq!(console.execute(&mut world, "spawn Mana").unwrap_err().to_string(), "spawn: unknown component \"Mana\"");
+    assert_eq!(console.execute(&mut world, "destroy 5").unwrap_err().to_string(), "destroy: no entity 5");
+    assert!(console.execute(&mut world, "help").unwrap().contains("echo <text>"));
+}
+
+#[test]
+fn test_console_quoted_value() {
+    let mut world = make_world();
+    let mut console = Console::new();
+
+    console.execute(&mut world, "spawn Health Name").unwrap();
+    assert_eq!(console.execute(&mut world, "set 1 Name.s \"50\"").unwrap(), "Name.s = \"50\"");
+    assert_eq!(console.execute(&mut world, "set 1 Name.s \"\"").unwrap(), "Name.s = \"\"");
+    assert_eq!(console.execute(&mut world, "set 1 Health.hp \"50\"").unwrap_err().to_string(),
+               "set: \"Health.hp\" expects integer");
+    assert_eq!(console.execute(&mut world, "set 1 Name.s 50").unwrap_err().to_string(),
+               "set: \"Name.s\" expects string");
+}
+
+#[test]
+#[should_panic(expected = "Command help is reserved")]
+fn test_register_help() {
+    let mut console = Console::empty();
+    console.register("help", "help - my help", |_, _| Ok(String::new()));
+}
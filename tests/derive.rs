@@ -0,0 +1,183 @@
+#![cfg(feature = "derive")]
+
+extern crate tinyecs;
+
+use std::any::TypeId;
+use tinyecs::*;
+
+#[derive(Component, Clone, Debug)]
+#[component(name = "Pos", clone, debug, storage = "boxed")]
+pub struct Position(i32);
+
+#[derive(Component)]
+pub struct Velocity(i32);
+
+#[derive(Component)]
+#[component(storage = "sync")]
+pub struct Progress(u32);
+
+#[system]
+fn move_system(pos : &mut Position, vel : &Velocity) {
+    pos.0 += vel.0;
+}
+
+#[test]
+fn derive_registers_component() {
+    let mut world = World::new();
+    world.register_component::<Position>();
+    world.register_component::<Velocity>();
+
+    let registration = world.registry().get_by_name("Pos").unwrap();
+    assert!(registration.type_id == TypeId::of::<Position>());
+    assert!(registration.clone.is_some());
+    assert!(registration.debug.is_some());
+    let registration = world.registry().get_by_name("Velocity").unwrap();
+    assert!(registration.clone.is_none());
+    assert!(Position::STORAGE == Storage::Boxed);
+}
+
+#[test]
+fn sync_storage() {
+    let entity = Entity::new(0);
+    entity.add_component(Progress(10));
+    let lock = entity.sync_component::<Progress>();
+    ::std::thread::spawn(move || lock.write().unwrap().0 += 90).join().unwrap();
+    assert_eq!(entity.read_sync_component::<Progress>().0, 100);
+}
+
+#[test]
+fn system_attribute() {
+    let mut world = World::new();
+    let id = {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.create_entity();
+        entity.add_component(Position(1));
+        entity.add_component(Velocity(2));
+        entity.id
+    };
+    let still = {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.create_entity();
+        entity.add_component(Position(1));
+        entity.id
+    };
+    world.set_system(MoveSystem::new());
+    world.update_with_delta(0.0);
+    world.update_with_delta(0.0);
+
+    let mut entity_manager = world.entity_manager();
+    assert_eq!(entity_manager.try_get_entity(id).unwrap().get_component::<Position>().0, 5);
+    assert_eq!(entity_manager.try_get_entity(still).unwrap().get_component::<Position>().0, 1);
+}
+
+pub struct Gravity(i32);
+
+#[derive(Component)]
+pub struct Boost(i32);
+
+#[system(FallSystem)]
+fn fall(pos : &mut Position, boost : Option<&mut Boost>, #[resource] gravity : &Gravity, world : &mut WorldHandle) {
+    pos.0 -= gravity.0;
+    if let Some(boost) = boost {
+        pos.0 += boost.0;
+        boost.0 = 0;
+    }
+    world.resources.insert(Velocity(pos.0));
+}
+
+#[test]
+fn system_attribute_params() {
+    let mut world = World::new();
+    world.resources().insert(Gravity(1));
+    let (plain, boosted) = {
+        let mut entity_manager = world.entity_manager();
+        let plain = entity_manager.create_entity();
+        plain.add_component(Position(10));
+        let plain = plain.id;
+        let boosted = entity_manager.create_entity();
+        boosted.add_component(Position(10));
+        boosted.add_component(Boost(5));
+        (plain, boosted.id)
+    };
+    world.set_system(FallSystem::new());
+    world.update_with_delta(0.0);
+    world.update_with_delta(0.0);
+
+    let mut entity_manager = world.entity_manager();
+    assert_eq!(entity_manager.try_get_entity(plain).unwrap().get_component::<Position>().0, 8);
+    assert_eq!(entity_manager.try_get_entity(boosted).unwrap().get_component::<Position>().0, 13);
+    assert!(world.resources().contains::<Velocity>());
+}
+
+#[derive(Component)]
+pub struct Camera;
+
+#[derive(Component)]
+pub struct Enemy;
+
+#[system]
+fn follow_camera(pos : &mut Position, _follower : &Boost, camera : Single<(&Camera, &Position)>, enemies : Query<&Enemy>) {
+    let (_, ref camera_pos) = *camera;
+    pos.0 = camera_pos.0 + enemies.len() as i32;
+}
+
+#[system]
+fn count_cameras(pos : &mut Position, _follower : &Boost, camera : OptionalSingle<&Camera>) {
+    if camera.is_some() {
+        pos.0 += 1;
+    }
+}
+
+fn query_world() -> (World, i32) {
+    let mut world = World::new();
+    let follower = {
+        let mut entity_manager = world.entity_manager();
+        let follower = entity_manager.create_entity();
+        follower.add_component(Position(0));
+        follower.add_component(Boost(0));
+        let follower = follower.id;
+        for _ in 0 .. 2 {
+            entity_manager.create_entity().add_component(Enemy);
+        }
+        follower
+    };
+    (world, follower)
+}
+
+#[test]
+fn system_queries() {
+    let (mut world, follower) = query_world();
+    world.set_system(CountCameras::new());
+    world.update_with_delta(0.0);
+    {
+        let mut entity_manager = world.entity_manager();
+        assert_eq!(entity_manager.try_get_entity(follower).unwrap().get_component::<Position>().0, 0);
+        let camera = entity_manager.create_entity();
+        camera.add_component(Camera);
+        camera.add_component(Position(10));
+    }
+    world.set_system(FollowCamera::new());
+    world.update_with_delta(0.0);
+
+    let mut entity_manager = world.entity_manager();
+    assert_eq!(entity_manager.try_get_entity(follower).unwrap().get_component::<Position>().0, 12);
+}
+
+#[test]
+fn single_spawned_late() {
+    let (mut world, follower) = query_world();
+    world.set_system(FollowCamera::new());
+    // no camera yet - system skips the frame
+    world.update_with_delta(0.0);
+    {
+        let mut entity_manager = world.entity_manager();
+        assert_eq!(entity_manager.try_get_entity(follower).unwrap().get_component::<Position>().0, 0);
+        let camera = entity_manager.create_entity();
+        camera.add_component(Camera);
+        camera.add_component(Position(10));
+    }
+    world.update_with_delta(0.0);
+
+    let mut entity_manager = world.entity_manager();
+    assert_eq!(entity_manager.try_get_entity(follower).unwrap().get_component::<Position>().0, 12);
+}
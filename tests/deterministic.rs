@@ -0,0 +1,84 @@
+extern crate tinyecs;
+
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::cell::RefCell;
+use tinyecs::*;
+
+pub struct Position(f32);
+impl Component for Position {}
+impl Hash for Position {
+    fn hash<H : Hasher>(&self, state : &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// Order dependent: every entity moves by the sum of all previously processed ids.
+pub struct MoveSystem {
+    sum   : i32,
+    order : Rc<RefCell<Vec<i32>>>
+}
+impl System for MoveSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Position)
+    }
+    fn process_w(&mut self, entity : &mut Entity, world : &mut WorldHandle) {
+        self.sum += entity.id;
+        entity.get_component::<Position>().0 += self.sum as f32 * world.delta;
+        self.order.borrow_mut().push(entity.id);
+    }
+}
+
+fn simulate() -> (u64, Vec<i32>) {
+    let order = Rc::new(RefCell::new(vec![]));
+
+    let mut world = World::new();
+    world.set_deterministic(true);
+    world.register_hash::<Position>();
+    world.set_system(MoveSystem { sum : 0, order : order.clone() });
+    {
+        let mut entity_manager = world.entity_manager();
+        for i in 0 .. 100 {
+            let e = entity_manager.create_entity();
+            e.add_component(Position(i as f32));
+            e.refresh();
+        }
+    }
+    for _ in 0 .. 10 {
+        world.update_with_delta(0.016);
+    }
+
+    let order = order.borrow().clone();
+    (world.checksum(), order)
+}
+
+#[test]
+fn test_deterministic_update() {
+    let (checksum, order) = simulate();
+
+    assert_eq!(order.len(), 1000);
+    assert_eq!(order[.. 100], (1 .. 101).collect::<Vec<_>>()[..]);
+    assert_eq!(simulate(), (checksum, order));
+}
+
+#[test]
+fn test_checksum() {
+    let mut world = World::new();
+    world.register_hash::<Position>();
+    let empty = world.checksum();
+
+    let id = {
+        let mut entity_manager = world.entity_manager();
+        let e = entity_manager.create_entity();
+        e.add_component(Position(1.0));
+        e.id
+    };
+    let one = world.checksum();
+    assert!(one != empty);
+
+    world.entity_manager().try_get_entity(id).unwrap().get_component::<Position>().0 = 2.0;
+    assert!(world.checksum() != one);
+
+    world.entity_manager().try_get_entity(id).unwrap().get_component::<Position>().0 = 1.0;
+    assert_eq!(world.checksum(), one);
+}
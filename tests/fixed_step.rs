@@ -0,0 +1,63 @@
+extern crate tinyecs;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use tinyecs::*;
+
+pub struct Body;
+impl Component for Body {}
+
+/// Records (delta, alpha) for each process call.
+pub struct RecordSystem {
+    log : Rc<RefCell<Vec<(f32, f32)>>>
+}
+impl System for RecordSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Body)
+    }
+    fn process_w(&mut self, _ : &mut Entity, world : &mut WorldHandle) {
+        self.log.borrow_mut().push((world.delta, world.alpha));
+    }
+}
+
+#[test]
+fn test_run_fixed() {
+    let fixed = Rc::new(RefCell::new(vec![]));
+    let variable = Rc::new(RefCell::new(vec![]));
+
+    let mut world = World::new();
+    world.set_system_in_group(SystemGroup::FIXED, RecordSystem { log : fixed.clone() });
+    world.set_system(RecordSystem { log : variable.clone() });
+    {
+        let mut entity_manager = world.entity_manager();
+        entity_manager.create_entity().add_component(Body);
+    }
+
+    assert_eq!(world.run_fixed_with_delta(0.25, 5, 0.6), 2);
+    assert_eq!(*fixed.borrow(), vec![(0.25, 1.0), (0.25, 1.0)]);
+    assert_eq!(variable.borrow().len(), 1);
+    assert_eq!(variable.borrow()[0].0, 0.6);
+    assert!((variable.borrow()[0].1 - 0.4).abs() < 1e-5);
+
+    // accumulated 0.1 + 0.1 is still less than a step
+    assert_eq!(world.run_fixed_with_delta(0.25, 5, 0.1), 0);
+    assert_eq!(fixed.borrow().len(), 2);
+    assert_eq!(variable.borrow().len(), 2);
+
+    // frame spike: no more than max_substeps, the rest is dropped
+    assert_eq!(world.run_fixed_with_delta(0.25, 4, 10.0), 4);
+    assert!(variable.borrow()[2].1 < 1.0);
+    assert_eq!(world.run_fixed_with_delta(0.25, 4, 0.0), 0);
+
+    // plain update runs every group once
+    world.update_with_delta(0.5);
+    assert_eq!(fixed.borrow().len(), 7);
+    assert_eq!(*variable.borrow().last().unwrap(), (0.5, 1.0));
+}
+
+#[test]
+#[should_panic(expected = "Fixed step should be positive, got 0")]
+fn test_run_fixed_zero_step() {
+    let mut world = World::new();
+    world.run_fixed_with_delta(0.0, 5, 0.1);
+}
@@ -0,0 +1,72 @@
+extern crate tinyecs;
+
+use std::rc::Rc;
+use std::cell::Cell;
+use tinyecs::*;
+
+pub struct Position(i32);
+impl Component for Position {}
+
+pub struct Velocity(i32);
+impl Component for Velocity {}
+
+pub struct Frozen;
+impl Component for Frozen {}
+
+fn make_world() -> (World, i32, i32) {
+    let mut world = World::new();
+    let mut entity_manager = world.entity_manager();
+    let moving = entity_manager.create_entity();
+    moving.add_component(Position(0));
+    moving.add_component(Velocity(2));
+    let moving = moving.id;
+    let frozen = entity_manager.create_entity();
+    frozen.add_component(Position(0));
+    frozen.add_component(Velocity(2));
+    frozen.add_component(Frozen);
+    let frozen = frozen.id;
+    (world, moving, frozen)
+}
+
+fn position(world : &mut World, id : i32) -> i32 {
+    world.entity_manager().try_get_entity(id).unwrap().get_component::<Position>().0
+}
+
+#[test]
+fn test_fn_system() {
+    let (mut world, moving, frozen) = make_world();
+    let spawned = Rc::new(Cell::new(0));
+    let counter = spawned.clone();
+
+    world.add_fn_system(aspect_all!(Position, Velocity).except::<Frozen>(), move |entity, world| {
+        entity.get_component::<Position>().0 += entity.get_component::<Velocity>().0;
+        world.entity_manager.create_entity();
+        counter.set(counter.get() + 1);
+    });
+    world.update();
+    world.update();
+
+    assert_eq!(position(&mut world, moving), 4);
+    assert_eq!(position(&mut world, frozen), 0);
+    assert_eq!(spawned.get(), 2);
+}
+
+#[test]
+fn test_components_system() {
+    let (mut world, moving, frozen) = make_world();
+
+    let handle = world.add_components_system(|pos : &mut Position, vel : &Velocity| {
+        pos.0 += vel.0;
+    });
+    world.add_components_system(|vel : &mut Velocity, _ : &Frozen| {
+        vel.0 = 0;
+    });
+    world.update();
+    world.update();
+    assert_eq!(position(&mut world, moving), 4);
+    assert_eq!(position(&mut world, frozen), 2);
+
+    world.remove_system(handle);
+    world.update();
+    assert_eq!(position(&mut world, moving), 4);
+}
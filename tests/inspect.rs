@@ -0,0 +1,101 @@
+extern crate tinyecs;
+
+use tinyecs::*;
+
+#[derive(Debug)]
+pub struct Position(i32);
+impl Component for Position {}
+
+pub struct Velocity;
+impl Component for Velocity {}
+
+pub struct Frozen;
+impl Component for Frozen {}
+
+pub struct MoveSystem;
+impl System for MoveSystem {
+    fn aspect(&self) -> Aspect {
+        Aspect::all2::<Position, Velocity>().except::<Frozen>()
+    }
+}
+
+pub struct PositionSystem;
+impl System for PositionSystem {
+    fn aspect(&self) -> Aspect {
+        Aspect::all::<Position>()
+    }
+}
+
+#[test]
+fn test_inspect_entity() {
+    let mut world = World::new();
+    world.register_debug::<Position>();
+    world.registry_mut().register(::std::any::TypeId::of::<Frozen>(), "Frozen");
+    world.registry_mut().register(::std::any::TypeId::of::<Velocity>(), "Velocity");
+    world.set_system(MoveSystem);
+    world.set_system(PositionSystem);
+    let id = {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.create_entity();
+        entity.add_component(Position(3));
+        entity.add_component(Frozen);
+        entity.id
+    };
+    world.update_with_delta(0.0);
+
+    let report = world.inspect_entity(id).unwrap();
+    assert_eq!(report.components.len(), 2);
+    assert_eq!(report.components[0].name, "Frozen");
+    assert_eq!(report.components[1].value, Some("Position(3)".to_string()));
+
+    assert!(!report.systems[0].processed);
+    assert_eq!(report.systems[0].aspect.missing, vec!["Velocity".to_string()]);
+    assert_eq!(report.systems[0].aspect.excluded, vec!["Frozen".to_string()]);
+    assert!(report.systems[1].processed);
+
+    let text = world.inspect().to_string();
+    assert!(text.contains("Position(3)"));
+    assert!(text.contains("MoveSystem, missing: Velocity; excluded: Frozen"));
+    assert!(world.inspect_entity(id + 1).is_none());
+    assert_eq!(world.entity_manager().try_get_entity(id).unwrap().get_component::<Position>().0, 3);
+}
+
+pub struct Walking;
+impl Component for Walking {}
+
+pub struct Flying;
+impl Component for Flying {}
+
+pub struct TravelSystem;
+impl System for TravelSystem {
+    fn aspect(&self) -> Aspect {
+        Aspect::all::<Position>().any_of2::<Walking, Flying>()
+    }
+}
+
+#[test]
+fn test_why_not() {
+    let mut world = World::new();
+    world.registry_mut().register(::std::any::TypeId::of::<Position>(), "Position");
+    world.registry_mut().register(::std::any::TypeId::of::<Walking>(), "Walking");
+    world.registry_mut().register(::std::any::TypeId::of::<Flying>(), "Flying");
+    let handle = world.set_system(TravelSystem);
+    let (walker, idle) = {
+        let mut entity_manager = world.entity_manager();
+        let walker = entity_manager.create_entity();
+        walker.add_component(Position(0));
+        walker.add_component(Walking);
+        let walker = walker.id;
+        let idle = entity_manager.create_entity();
+        idle.add_component(Frozen);
+        (walker, idle.id)
+    };
+
+    assert!(world.why_not(handle, walker).unwrap().is_match());
+    let report = world.why_not(handle, idle).unwrap();
+    assert!(!report.is_match());
+    assert_eq!(report.missing, vec!["Position".to_string()]);
+    assert_eq!(report.unmet_any_of, vec![vec!["Walking".to_string(), "Flying".to_string()]]);
+    assert_eq!(report.to_string(), "missing: Position; none of: Walking | Flying");
+    assert!(world.why_not(handle, idle + 1).is_none());
+}
@@ -0,0 +1,120 @@
+extern crate tinyecs;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use tinyecs::*;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Position(f32);
+impl Component for Position {}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Input {
+    pub dx : f32
+}
+
+pub struct MoveSystem;
+impl System for MoveSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Position)
+    }
+    fn process_w(&mut self, entity : &mut Entity, world : &mut WorldHandle) {
+        let input = *world.resources.get::<Input>();
+        entity.get_component::<Position>().0 += input.dx * world.delta;
+    }
+}
+
+fn position(world : &mut World, id : i32) -> f32 {
+    let mut entity_manager = world.entity_manager();
+    let entity = entity_manager.get_entities_by_ids(&[id].iter().cloned().collect()).pop().unwrap();
+    let x = entity.get_component::<Position>().0;
+    x
+}
+
+#[test]
+fn test_input_buffer() {
+    let mut inputs = InputBuffer::new(3);
+    assert!(inputs.is_empty());
+    for tick in 1 .. 5 {
+        inputs.push(tick, tick as i32 * 10);
+    }
+    assert_eq!(inputs.len(), 3);
+    assert_eq!(inputs.get(1), None);
+    assert_eq!(inputs.get(4), Some(&40));
+
+    inputs.acknowledge(2);
+    assert_eq!(inputs.since(2).collect::<Vec<_>>(), vec![(3, &30), (4, &40)]);
+    assert_eq!(inputs.since(3).count(), 1);
+    assert_eq!(inputs.latest_tick(), Some(4));
+}
+
+#[test]
+#[should_panic(expected = "Input for tick 2 pushed after tick 3")]
+fn test_input_buffer_order() {
+    let mut inputs = InputBuffer::new(3);
+    inputs.push(3, ());
+    inputs.push(2, ());
+}
+
+#[test]
+fn test_reconcile() {
+    let mut world = World::new();
+    world.register_clone::<Position>();
+    world.register_clone::<Input>();
+    world.set_system_in_group(SystemGroup::FIXED, MoveSystem);
+    let id = {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.create_entity();
+        entity.add_component(Position(0.));
+        entity.refresh();
+        entity.id
+    };
+    world.resources().insert(Input { dx : 0. });
+    world.update_group(SystemGroup::FIXED, 1.);
+
+    let corrections = Rc::new(RefCell::new(vec![]));
+    let mut prediction = Prediction::new(&world, SystemGroup::FIXED, 1.);
+    {
+        let corrections = corrections.clone();
+        prediction.on_correction::<Position, _>(move |entity, predicted, corrected| {
+            corrections.borrow_mut().push((entity.id, predicted.0, corrected.0));
+        });
+    }
+
+    for _ in 0 .. 3 {
+        prediction.predict(&mut world, Input { dx : 1. });
+    }
+    assert_eq!(prediction.tick(), 3);
+    assert_eq!(position(&mut world, id), 3.);
+
+    // server processed tick 1 and stopped the entity at 0.5
+    let resimulated = prediction.reconcile(&mut world, 1, |world| {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.get_entities_by_ids(&[id].iter().cloned().collect()).pop().unwrap();
+        entity.get_component::<Position>().0 = 0.5;
+    });
+    assert_eq!(resimulated, 2);
+    assert_eq!(prediction.inputs.len(), 2);
+    assert_eq!(position(&mut world, id), 2.5);
+    assert_eq!(*corrections.borrow(), vec![(id, 3., 2.5)]);
+
+    // server agrees with prediction - nothing to smooth
+    prediction.predict(&mut world, Input { dx : 1. });
+    let resimulated = prediction.reconcile(&mut world, 2, |world| {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.get_entities_by_ids(&[id].iter().cloned().collect()).pop().unwrap();
+        entity.get_component::<Position>().0 = 1.5;
+    });
+    assert_eq!(resimulated, 2);
+    assert_eq!(position(&mut world, id), 3.5);
+    assert_eq!(corrections.borrow().len(), 1);
+
+    // late packet of tick 1 arrives after tick 2 - stale, ignored
+    assert_eq!(prediction.confirmed_tick(), 2);
+    let resimulated = prediction.reconcile(&mut world, 1, |_| panic!("stale state applied"));
+    assert_eq!(resimulated, 0);
+    assert_eq!(prediction.reconcile(&mut world, 2, |_| panic!("stale state applied")), 0);
+    assert_eq!(prediction.inputs.len(), 2);
+    assert_eq!(position(&mut world, id), 3.5);
+    assert_eq!(corrections.borrow().len(), 1);
+}
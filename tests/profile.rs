@@ -0,0 +1,43 @@
+extern crate tinyecs;
+
+use std::thread;
+use std::time::Duration;
+use tinyecs::*;
+
+pub struct Sleepy;
+impl Component for Sleepy {}
+
+pub struct SleepSystem;
+impl System for SleepSystem {
+    fn aspect(&self) -> Aspect {
+        Aspect::all::<Sleepy>()
+    }
+    fn process_one(&mut self, _ : &mut Entity) {
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_system_stats() {
+    let mut world = World::new();
+    world.entity_manager().create_entity().add_component(Sleepy);
+    let handle = world.set_system(SleepSystem);
+    for _ in 0 .. 3 {
+        world.update_with_delta(0.0);
+    }
+
+    let stats = world.system_stats();
+    assert_eq!(stats.len(), 1);
+    assert!(stats[0].name.ends_with("SleepSystem"));
+    assert!(stats[0].handle == handle);
+
+    let process = stats[0].phase(Phase::Process);
+    assert_eq!(process.calls(), 3);
+    assert!(process.average() >= Duration::from_millis(1));
+    assert!(process.max() >= process.average());
+    assert!(process.percentile(50.0) <= process.max());
+    assert_eq!(process.percentile(100.0), process.max());
+    assert_eq!(stats[0].phase(Phase::BeginFrame).calls(), 3);
+    assert_eq!(stats[0].phase(Phase::Added).calls(), 1);
+    assert_eq!(stats[0].phase(Phase::Removed).calls(), 0);
+}
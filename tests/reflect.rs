@@ -0,0 +1,94 @@
+#![cfg(feature = "derive")]
+
+extern crate tinyecs;
+
+use tinyecs::*;
+
+#[derive(Reflect, Clone, Copy)]
+pub struct Vec2 {
+    pub x : f32,
+    pub y : f32
+}
+
+#[derive(Component, Reflect)]
+#[component(reflect)]
+pub struct Transform {
+    pub position : Vec2,
+    pub rotation : f32,
+    #[reflect(skip)]
+    pub dirty    : Option<u32>
+}
+
+#[derive(Component, Reflect)]
+#[component(reflect)]
+pub struct Health(i32);
+
+fn make_entity() -> (World, Entity) {
+    let mut world = World::new();
+    world.register_component::<Transform>();
+    world.register_component::<Health>();
+
+    let entity = Entity::new(0);
+    entity.add_component(Transform { position : Vec2 { x : 1.0, y : 2.0 }, rotation : 0.0, dirty : None });
+    entity.add_component(Health(100));
+    (world, entity)
+}
+
+#[test]
+fn test_reflect_paths() {
+    let (world, entity) = make_entity();
+
+    assert_eq!(entity.get_path(world.registry(), "Transform.position.y"), Ok(ReflectValue::Float(2.0)));
+    entity.set_path(world.registry(), "Transform.position.x", ReflectValue::parse("5")).unwrap();
+    entity.set_path(world.registry(), "Health.0", ReflectValue::Int(50)).unwrap();
+
+    assert_eq!(entity.get_component::<Transform>().position.x, 5.0);
+    assert_eq!(entity.get_component::<Health>().0, 50);
+    assert!(entity.get_component::<Transform>().dirty.is_none());
+
+    let mut transform = entity.reflect_component(world.registry(), "Transform").unwrap();
+    assert_eq!(transform.field_names(), vec!["position", "rotation"]);
+    transform.set("position", Vec2 { x : 0.0, y : 0.0 }).unwrap();
+    assert_eq!(*transform.get::<f32>("position.y").unwrap(), 0.0);
+}
+
+#[test]
+fn test_reflect_errors() {
+    let (world, entity) = make_entity();
+
+    assert_eq!(entity.get_path(world.registry(), "Mana.value"), Err(ReflectError::NoComponent("Mana".to_string())));
+    assert_eq!(entity.get_path(world.registry(), "Transform.scale"), Err(ReflectError::NoField("Transform.scale".to_string())));
+    assert_eq!(entity.get_path(world.registry(), "Transform.position"), Err(ReflectError::NotAValue("Transform.position".to_string())));
+    assert_eq!(entity.set_path(world.registry(), "Health.0", ReflectValue::Bool(true)).unwrap_err().to_string(),
+               "\"Health.0\" expects integer");
+    assert_eq!(entity.set_path(world.registry(), "Health.0", ReflectValue::Int(1 << 40)).unwrap_err().to_string(),
+               "\"Health.0\" expects integer in range of i32");
+    assert_eq!(entity.get_path(world.registry(), "Health.0"), Ok(ReflectValue::Int(100)));
+
+    let health = entity.get_component::<Health>();
+    assert_eq!(entity.get_path(world.registry(), "Health.0"), Err(ReflectError::Borrowed("Health".to_string())));
+    drop(health);
+    let _transform = entity.reflect_component(world.registry(), "Transform").unwrap();
+    assert_eq!(entity.get_path(world.registry(), "Transform.rotation"), Err(ReflectError::Borrowed("Transform".to_string())));
+    assert_eq!(entity.get_path(world.registry(), "Health.0"), Ok(ReflectValue::Int(100)));
+}
+
+#[derive(Component, Reflect, Default)]
+#[component(name = "Mana", default, reflect)]
+pub struct ManaPool {
+    pub v : i32
+}
+
+#[test]
+fn test_reflect_registered_name() {
+    let mut world = World::new();
+    world.register_component::<ManaPool>();
+    let mut console = Console::new();
+
+    assert_eq!(console.execute(&mut world, "spawn Mana").unwrap(), "spawned 1");
+    assert_eq!(console.execute(&mut world, "set 1 Mana.v 5").unwrap(), "Mana.v = 5");
+    assert_eq!(console.execute(&mut world, "get 1 Mana.v").unwrap(), "5");
+
+    let mut entity_manager = world.entity_manager();
+    assert_eq!(entity_manager.try_get_entity(1).unwrap().get_component::<ManaPool>().v, 5);
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "remote")]
+
+extern crate tinyecs;
+extern crate serde_json;
+
+use std::any::Any;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+use serde_json::Value;
+use tinyecs::*;
+
+#[derive(Debug)]
+pub struct Health {
+    pub hp : i32
+}
+impl Component for Health {}
+
+impl Reflect for Health {
+    fn fields() -> Vec<FieldInfo> {
+        vec![FieldInfo { name : "hp", type_name : "i32" }]
+    }
+    fn field_names(&self) -> Vec<&'static str> {
+        vec!["hp"]
+    }
+    fn field(&self, name : &str) -> Option<&dyn Reflect> {
+        if name == "hp" { Some(&self.hp) } else { None }
+    }
+    fn field_mut(&mut self, name : &str) -> Option<&mut dyn Reflect> {
+        if name == "hp" { Some(&mut self.hp) } else { None }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+struct Connection {
+    reader : BufReader<TcpStream>,
+    writer : TcpStream
+}
+
+impl Connection {
+    fn request(&mut self, server : &mut RemoteServer, world : &mut World, request : &str) -> Value {
+        self.writer.write_all(request.as_bytes()).unwrap();
+        self.writer.write_all(b"\n").unwrap();
+        let mut line = String::new();
+        while !line.ends_with('\n') {
+            server.poll(world);
+            // read times out while server has not answered yet
+            let _ = self.reader.read_line(&mut line);
+        }
+        serde_json::from_str(&line).unwrap()
+    }
+}
+
+#[test]
+fn test_remote_protocol() {
+    let mut world = World::new();
+    world.register_debug::<Health>();
+    world.register_reflect::<Health>();
+    world.entity_manager().create_entity().add_component(Health { hp : 10 });
+
+    let mut server = RemoteServer::localhost(0).unwrap();
+    let stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    stream.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+    let mut connection = Connection { reader : BufReader::new(stream.try_clone().unwrap()), writer : stream };
+
+    let response = connection.request(&mut server, &mut world, r#"{"id": 1, "cmd": "entities"}"#);
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"][0]["components"][0], "Health");
+
+    let response = connection.request(&mut server, &mut world, r#"{"cmd": "set", "entity": 1, "path": "Health.hp", "value": 50}"#);
+    assert_eq!(response["ok"], true);
+    let response = connection.request(&mut server, &mut world, r#"{"cmd": "components", "entity": 1}"#);
+    assert_eq!(response["result"][0]["debug"], "Health { hp: 50 }");
+    assert_eq!(response["result"][0]["fields"]["hp"], 50);
+
+    let response = connection.request(&mut server, &mut world, r#"{"cmd": "fly"}"#);
+    assert_eq!(response["ok"], false);
+    assert_eq!(response["error"], "unknown cmd \"fly\"");
+
+    // ids beyond i32 are rejected, not wrapped to entity 1
+    let response = connection.request(&mut server, &mut world, r#"{"cmd": "components", "entity": 4294967297}"#);
+    assert_eq!(response["ok"], false);
+    assert_eq!(response["error"], "entity out of range");
+
+    connection.request(&mut server, &mut world, r#"{"cmd": "step", "frames": 2}"#);
+    assert!(server.is_paused());
+    for _ in 0 .. 3 {
+        server.update_with_delta(&mut world, 0.5);
+    }
+    assert_eq!(world.resources().get::<Time>().frame_count, 2);
+
+    // frame counts beyond u32 are clamped, not wrapped
+    connection.request(&mut server, &mut world, r#"{"cmd": "step", "frames": 4294967297}"#);
+    connection.request(&mut server, &mut world, r#"{"cmd": "step", "frames": 4294967295}"#);
+    for _ in 0 .. 3 {
+        server.update_with_delta(&mut world, 0.5);
+    }
+    assert_eq!(world.resources().get::<Time>().frame_count, 5);
+
+    let response = connection.request(&mut server, &mut world, r#"{"cmd": "systems"}"#);
+    assert_eq!(response["result"], Value::Array(vec![]));
+}
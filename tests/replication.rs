@@ -0,0 +1,261 @@
+#![cfg(feature = "serde")]
+
+extern crate tinyecs;
+#[macro_use] extern crate serde;
+
+use std::cell::Cell;
+use tinyecs::*;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Position {
+    pub x : f32,
+    pub y : f32
+}
+impl Component for Position {}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Health {
+    pub hp : i32
+}
+impl Component for Health {}
+
+thread_local! {
+    static SERIALIZED : Cell<u32> = const { Cell::new(0) };
+}
+
+/// Counts its serializations.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Counted(i32);
+impl Component for Counted {}
+
+impl serde::Serialize for Counted {
+    fn serialize<S : serde::Serializer>(&self, serializer : S) -> Result<S::Ok, S::Error> {
+        SERIALIZED.with(|count| count.set(count.get() + 1));
+        self.0.serialize(serializer)
+    }
+}
+
+/// Server only component, never replicated.
+pub struct Brain;
+impl Component for Brain {}
+
+fn registered_world() -> World {
+    let mut world = World::new();
+    world.register_replicated::<Position>("Position");
+    world.register_replicated::<Health>("Health");
+    world
+}
+
+fn sync(server : &mut ReplicationServer, client_id : ClientId, server_world : &World,
+        client : &mut ReplicationClient, client_world : &mut World,
+        server_end : &mut MemoryTransport, client_end : &mut MemoryTransport) {
+    server.capture(server_world).unwrap();
+    server.send(client_id, server_end).unwrap();
+    client.receive(client_world, client_end).unwrap();
+    server.receive_acks(client_id, server_end);
+}
+
+#[test]
+fn test_replication_deltas() {
+    let mut world = registered_world();
+    let (a, b) = {
+        let mut entity_manager = world.entity_manager();
+        let a = entity_manager.create_entity();
+        a.add_component(Position { x : 1., y : 2. });
+        a.add_component(Health { hp : 10 });
+        a.add_component(Brain);
+        a.refresh();
+        let a = a.id;
+        let b = entity_manager.create_entity();
+        b.add_component(Position { x : 5., y : 5. });
+        b.refresh();
+        (a, b.id)
+    };
+    // entity without replicated components
+    world.entity_manager().create_entity().add_component(Brain);
+    world.update();
+
+    let mut server = ReplicationServer::new();
+    let client_id = server.add_client();
+    let (mut server_end, mut client_end) = MemoryTransport::pair();
+
+    let mut client_world = registered_world();
+    // local entity, unknown to server, keeps its id
+    client_world.entity_manager().create_entity().refresh();
+    let mut client = ReplicationClient::new();
+
+    server.capture(&world).unwrap();
+    server.send(client_id, &mut server_end).unwrap();
+    assert_eq!(client.receive(&mut client_world, &mut client_end).unwrap(), Some(1));
+    server.receive_acks(client_id, &mut server_end);
+    assert_eq!(server.acked(client_id), Some(1));
+
+    let local_a = client.local_id(a).unwrap();
+    let local_b = client.local_id(b).unwrap();
+    assert!(local_a != 1 && local_b != 1);
+    client_world.update();
+    assert_eq!(client_world.inspect().entities.len(), 3);
+    {
+        let entity = client_world.inspect_entity(local_a).unwrap();
+        assert_eq!(entity.components.len(), 2);
+    }
+
+    // nothing changed - delta is much smaller than full state, sent to new client
+    server.capture(&world).unwrap();
+    let new_client = server.add_client();
+    let full = server.delta_for(new_client).unwrap().len();
+    let empty = server.delta_for(client_id).unwrap().len();
+    assert!(empty < full / 2, "{} vs {}", empty, full);
+
+    // change, remove component and despawn
+    {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.get_entities_by_ids(&[a].iter().cloned().collect())
+            .pop().unwrap();
+        entity.get_component::<Position>().x = 3.;
+        entity.remove_component::<Health>();
+        entity.refresh();
+    }
+    assert!(world.remove_entity(b));
+    world.update();
+
+    sync(&mut server, client_id, &world, &mut client, &mut client_world, &mut server_end, &mut client_end);
+    client_world.update();
+    assert_eq!(client.tick(), Some(3));
+    assert_eq!(client.local_id(b), None);
+    let report = client_world.inspect_entity(local_a).unwrap();
+    assert_eq!(report.components.len(), 1);
+    assert!(client_world.inspect_entity(local_b).is_none());
+}
+
+#[test]
+fn test_replication_lost_and_reordered() {
+    let mut world = registered_world();
+    let id = {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.create_entity();
+        entity.add_component(Health { hp : 10 });
+        entity.refresh();
+        entity.id
+    };
+    world.update();
+
+    let mut server = ReplicationServer::new();
+    let client_id = server.add_client();
+    let mut client_world = registered_world();
+    let mut client = ReplicationClient::new();
+
+    server.capture(&world).unwrap();
+    let full = server.delta_for(client_id).unwrap();
+    let tick = client.apply(&mut client_world, &full).unwrap().unwrap();
+    server.ack(client_id, tick);
+
+    let set_hp = |world : &mut World, hp : i32| {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.get_entities_by_ids(&[id].iter().cloned().collect()).pop().unwrap();
+        entity.get_component::<Health>().hp = hp;
+    };
+
+    // two deltas against tick 1 sent before the ack arrives, delivered out of order
+    set_hp(&mut world, 5);
+    server.capture(&world).unwrap();
+    let second = server.delta_for(client_id).unwrap();
+    set_hp(&mut world, 10);
+    server.capture(&world).unwrap();
+    let third = server.delta_for(client_id).unwrap();
+
+    assert_eq!(client.apply(&mut client_world, &third).unwrap(), Some(3));
+    assert_eq!(client.apply(&mut client_world, &second).unwrap(), None);
+
+    let local = client.local_id(id).unwrap();
+    let mut entity_manager = client_world.entity_manager();
+    let entity = entity_manager.get_entities_by_ids(&[local].iter().cloned().collect()).pop().unwrap();
+    assert_eq!(entity.get_component::<Health>().hp, 10);
+}
+
+#[test]
+fn test_replication_errors() {
+    let mut server = ReplicationServer::new();
+    let client_id = server.add_client();
+    match server.delta_for(client_id) {
+        Err(ReplicationError::NothingCaptured) => {},
+        _ => panic!("expected NothingCaptured")
+    }
+    assert!(server.remove_client(client_id));
+    match server.delta_for(client_id) {
+        Err(ReplicationError::UnknownClient(_)) => {},
+        _ => panic!("expected UnknownClient")
+    }
+
+    // client does not know the component
+    let mut world = registered_world();
+    world.entity_manager().create_entity().add_component(Health { hp : 1 });
+    let client_id = server.add_client();
+    server.capture(&world).unwrap();
+    let delta = server.delta_for(client_id).unwrap();
+
+    let mut client_world = World::new();
+    match ReplicationClient::new().apply(&mut client_world, &delta) {
+        Err(ReplicationError::UnknownComponent(ref name)) if name == "Health" => {},
+        other => panic!("expected UnknownComponent, got {:?}", other)
+    }
+    assert!(client_world.inspect().entities.is_empty());
+}
+
+#[test]
+fn test_replication_change_tracking() {
+    let mut world = World::new();
+    world.register_replicated::<Counted>("Counted");
+    let (a, b) = {
+        let mut entity_manager = world.entity_manager();
+        let a = entity_manager.create_entity();
+        a.add_component(Counted(1));
+        a.refresh();
+        let a = a.id;
+        let b = entity_manager.create_entity();
+        b.add_component(Counted(2));
+        b.refresh();
+        (a, b.id)
+    };
+    world.update();
+
+    let mut server = ReplicationServer::new();
+    let client_id = server.add_client();
+    let (mut server_end, mut client_end) = MemoryTransport::pair();
+    let mut client_world = World::new();
+    client_world.register_replicated::<Counted>("Counted");
+    let mut client = ReplicationClient::new();
+
+    let serialized = || SERIALIZED.with(Cell::get);
+    sync(&mut server, client_id, &world, &mut client, &mut client_world, &mut server_end, &mut client_end);
+    assert_eq!(serialized(), 2);
+
+    // reading does not change the entity
+    let x = world.entity_manager().try_get_entity(a).unwrap().get_component::<Counted>().0;
+    assert_eq!(x, 1);
+    sync(&mut server, client_id, &world, &mut client, &mut client_world, &mut server_end, &mut client_end);
+    assert_eq!(serialized(), 2);
+
+    world.entity_manager().try_get_entity(a).unwrap().get_component::<Counted>().0 = 10;
+    sync(&mut server, client_id, &world, &mut client, &mut client_world, &mut server_end, &mut client_end);
+    assert_eq!(serialized(), 3);
+    let local_a = client.local_id(a).unwrap();
+    assert_eq!(client_world.entity_manager().try_get_entity(local_a).unwrap().get_component::<Counted>().0, 10);
+
+    // writes through the handed out lock are not tracked, entity is captured every time
+    {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.try_get_entity(b).unwrap();
+        entity.remove_component::<Counted>();
+        entity.refresh();
+    }
+    world.update();
+    world.entity_manager().try_get_entity(b).unwrap().add_sync_component(Counted(2));
+    let lock = world.entity_manager().try_get_entity(b).unwrap().sync_component::<Counted>();
+    sync(&mut server, client_id, &world, &mut client, &mut client_world, &mut server_end, &mut client_end);
+    lock.write().unwrap().0 = 20;
+    sync(&mut server, client_id, &world, &mut client, &mut client_world, &mut server_end, &mut client_end);
+    assert_eq!(serialized(), 5);
+    let local_b = client.local_id(b).unwrap();
+    assert_eq!(client_world.entity_manager().try_get_entity(local_b).unwrap().get_component::<Counted>().0, 20);
+}
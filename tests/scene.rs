@@ -0,0 +1,142 @@
+#![cfg(feature = "serde")]
+
+extern crate tinyecs;
+#[macro_use] extern crate serde;
+
+use tinyecs::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Position {
+    x : f32,
+    y : f32
+}
+impl Component for Position {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Health {
+    hp  : i32,
+    max : i32
+}
+impl Component for Health {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Name(String);
+impl Component for Name {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Player;
+impl Component for Player {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Path {
+    points : Vec<Position>
+}
+impl Component for Path {}
+
+fn make_world() -> World {
+    let mut world = World::new();
+    world.register_serde::<Position>("Position");
+    world.register_serde::<Health>("Health");
+    world.register_serde::<Name>("Name");
+    world.register_serde::<Player>("Player");
+    world.register_serde::<Path>("Path");
+    world
+}
+
+const SCENE : &str = r#"
+// enemies share this
+prefab Enemy {
+    Health(hp: 100, max: 100),
+    Position(x: 0.0, y: 0.0),
+}
+
+entity player {
+    Position(x: 1.0, y: -2.5),
+    Name("hero"),
+    Player,
+}
+
+entity guard : Enemy {
+    parent: player,
+    Position(x: 5),
+    Path(points: [Position(x: 0, y: 0), (x: 10, y: 0)]),
+}
+"#;
+
+#[test]
+fn test_spawn_scene() {
+    let mut world = make_world();
+    let ids = world.spawn_scene_str(SCENE).unwrap();
+
+    let mut entity_manager = world.entity_manager();
+    {
+        let player = entity_manager.try_get_entity(ids["player"]).unwrap();
+        assert_eq!(*player.get_component::<Position>(), Position { x : 1.0, y : -2.5 });
+        assert_eq!(*player.get_component::<Name>(), Name("hero".to_string()));
+        assert!(player.has_component::<Player>());
+    }
+    {
+        let guard = entity_manager.try_get_entity(ids["guard"]).unwrap();
+        assert_eq!(*guard.get_component::<Position>(), Position { x : 5.0, y : 0.0 });
+        assert_eq!(*guard.get_component::<Health>(), Health { hp : 100, max : 100 });
+        assert_eq!(guard.get_component::<Path>().points.len(), 2);
+        assert_eq!(guard.get_component::<Parent>().entity, ids["player"]);
+    }
+}
+
+fn error_at(source : &str) -> (usize, usize, String) {
+    match make_world().spawn_scene_str(source) {
+        Err(SceneError::Parse { line, column, message }) => (line, column, message),
+        _ => panic!("scene should fail to load")
+    }
+}
+
+#[test]
+fn test_scene_errors() {
+    assert_eq!(error_at("entity a {\n  Position(x: 1.0 y: 2.0)\n}").0, 2);
+    assert_eq!(error_at("entity a {\n  Position(x: 1.0 y: 2.0)\n}").1, 19);
+
+    let (line, column, message) = error_at("entity a {\n    Velocity(x: 1.0),\n}");
+    assert_eq!((line, column), (2, 5));
+    assert!(message.contains("Velocity"));
+
+    let (line, _, message) = error_at("entity a {\n Position(x: \"one\", y: 2.0),\n}");
+    assert_eq!(line, 2);
+    assert!(message.contains("Position"));
+
+    assert_eq!(error_at("entity a : Missing {}").1, 12);
+    assert_eq!(error_at("entity a { parent: b }").1, 20);
+}
+
+#[test]
+fn test_scene_save_load() {
+    let mut world = make_world();
+    let ids = world.spawn_scene_str(SCENE).unwrap();
+
+    let mut saved = vec![];
+    world.save(&mut saved, Format::Binary).unwrap();
+
+    // loaded entities get new ids after the existing ones
+    let report = world.load(&saved[..]).unwrap();
+    let player = report.ids[&ids["player"]];
+    let guard = report.ids[&ids["guard"]];
+    assert!(player != ids["player"]);
+
+    let mut entity_manager = world.entity_manager();
+    let guard = entity_manager.try_get_entity(guard).unwrap();
+    assert_eq!(guard.get_component::<Parent>().entity, player);
+}
+
+mod game {
+    /// User type with the name of a built-in one.
+    #[derive(Serialize, Deserialize)]
+    pub struct Parent;
+    impl ::tinyecs::Component for Parent {}
+}
+
+#[test]
+fn test_builtin_names_are_not_reserved() {
+    let mut world = World::new();
+    world.register_serde::<game::Parent>("Parent");
+    assert!(world.registry().get_by_name("tinyecs::Parent").is_some());
+}
@@ -0,0 +1,226 @@
+#![cfg(feature = "serde")]
+
+extern crate tinyecs;
+#[macro_use] extern crate serde;
+
+use tinyecs::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Position {
+    x : f32,
+    y : f32
+}
+impl Component for Position {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Name(String);
+impl Component for Name {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Score(i32);
+
+pub struct NotSaved;
+impl Component for NotSaved {}
+
+fn register(world : &mut World) {
+    world.register_serde::<Position>("Position");
+    world.register_serde::<Name>("Name");
+    world.register_serde::<Score>("Score");
+}
+
+fn make_world() -> World {
+    let mut world = World::new();
+    register(&mut world);
+    {
+        let mut entity_manager = world.entity_manager();
+        let e = entity_manager.create_entity();
+        e.add_component(Position { x : 1.0, y : 2.5 });
+        e.add_component(Name("player".to_string()));
+        e.refresh();
+
+        let e = entity_manager.create_entity();
+        e.add_component(Position { x : -3.0, y : 0.0 });
+        e.refresh();
+    }
+    world.resources().insert(Score(42));
+    world
+}
+
+fn check_loaded(mut world : World, bytes : &[u8]) {
+    let ids = world.load(bytes).unwrap().ids;
+    assert_eq!(ids.len(), 2);
+
+    let mut entity_manager = world.entity_manager();
+    {
+        let player = entity_manager.try_get_entity(ids[&1]).unwrap();
+        assert_eq!(*player.get_component::<Position>(), Position { x : 1.0, y : 2.5 });
+        assert_eq!(*player.get_component::<Name>(), Name("player".to_string()));
+    }
+    {
+        let other = entity_manager.try_get_entity(ids[&2]).unwrap();
+        assert_eq!(*other.get_component::<Position>(), Position { x : -3.0, y : 0.0 });
+        assert!(!other.has_component::<Name>());
+    }
+}
+
+#[test]
+fn test_save_load_json_and_binary() {
+    for &format in &[Format::Json, Format::Binary] {
+        let mut bytes = vec![];
+        make_world().save(&mut bytes, format).unwrap();
+
+        let mut world = World::new();
+        register(&mut world);
+        {
+            // loaded entities should not collide with existing ones
+            let mut entity_manager = world.entity_manager();
+            entity_manager.create_entity().refresh();
+        }
+        check_loaded(world, &bytes);
+    }
+}
+
+#[test]
+fn test_load_resources() {
+    let mut bytes = vec![];
+    make_world().save(&mut bytes, Format::Binary).unwrap();
+
+    let mut world = World::new();
+    register(&mut world);
+    world.load(&bytes[..]).unwrap();
+    assert_eq!(*world.resources().get::<Score>(), Score(42));
+}
+
+#[test]
+fn test_load_keeps_time() {
+    let mut bytes = vec![];
+    make_world().save(&mut bytes, Format::Binary).unwrap();
+
+    let mut world = World::new();
+    register(&mut world);
+    world.update_with_delta(0.5);
+    world.resources().get::<Time>().scale = 0.0;
+
+    world.load(&bytes[..]).unwrap();
+    assert_eq!(world.resources().get::<Time>().frame_count, 1);
+    assert_eq!(world.resources().get::<Time>().scale, 0.0);
+
+    world.load_with_time(&bytes[..]).unwrap();
+    assert_eq!(*world.resources().get::<Time>(), Time::default());
+}
+
+mod game {
+    /// User resource with the name of a built-in one.
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    pub struct Time(pub u32);
+}
+
+#[test]
+fn test_user_time_resource() {
+    let mut world = make_world();
+    world.register_serde::<game::Time>("Time");
+    world.resources().insert(game::Time(7));
+    let mut bytes = vec![];
+    world.save(&mut bytes, Format::Json).unwrap();
+
+    let mut loaded = World::new();
+    register(&mut loaded);
+    loaded.register_serde::<game::Time>("Time");
+    loaded.load(&bytes[..]).unwrap();
+    assert_eq!(*loaded.resources().get::<game::Time>(), game::Time(7));
+}
+
+#[test]
+fn test_unregistered_component() {
+    let mut world = make_world();
+    {
+        let mut entity_manager = world.entity_manager();
+        entity_manager.create_entity().add_component(NotSaved);
+    }
+    match world.save(vec![], Format::Json) {
+        Err(SerializeError::UnregisteredComponent { entity : 3, .. }) => {},
+        other => panic!("unexpected result: {:?}", other.map(|_| ()))
+    }
+
+    let mut bytes = vec![];
+    make_world().save(&mut bytes, Format::Json).unwrap();
+    let mut world = World::new();
+    world.register_serde::<Position>("Position");
+    match world.load(&bytes[..]) {
+        Err(SerializeError::UnknownComponent(ref name)) if name == "Name" || name == "Score" => {},
+        other => panic!("unexpected result: {:?}", other.map(|_| ()))
+    }
+}
+
+mod old {
+    #[derive(Serialize, Deserialize)]
+    pub struct Position {
+        pub x : f32
+    }
+    impl ::tinyecs::Component for Position {}
+}
+
+fn save_old_world() -> Vec<u8> {
+    let mut world = World::new();
+    world.register_serde::<old::Position>("Position");
+    world.register_serde::<Name>("Name");
+    {
+        let mut entity_manager = world.entity_manager();
+        let e = entity_manager.create_entity();
+        e.add_component(old::Position { x : 4.0 });
+        e.refresh();
+
+        let e = entity_manager.create_entity();
+        e.add_component(Name("no position".to_string()));
+        e.refresh();
+    }
+    let mut bytes = vec![];
+    world.save(&mut bytes, Format::Json).unwrap();
+    bytes
+}
+
+#[test]
+fn test_migrations() {
+    let bytes = save_old_world();
+
+    let mut world = World::new();
+    world.register_serde::<Name>("Name");
+    world.register_serde_versioned::<Position>("Position", 2);
+    world.register_migration("Position", 0, |mut data| {
+        data["y"] = 1.0.into();
+        Ok(data)
+    });
+    world.register_migration("Position", 1, |mut data| {
+        let x = data["x"].as_f64().ok_or("x is not a number")?;
+        data["x"] = (x * 2.0).into();
+        Ok(data)
+    });
+
+    let report = world.load(&bytes[..]).unwrap();
+    assert!(report.failed.is_empty());
+
+    let mut entity_manager = world.entity_manager();
+    let e = entity_manager.try_get_entity(report.ids[&1]).unwrap();
+    assert_eq!(*e.get_component::<Position>(), Position { x : 8.0, y : 1.0 });
+}
+
+#[test]
+fn test_failed_migrations() {
+    let bytes = save_old_world();
+
+    let mut world = World::new();
+    world.register_serde::<Name>("Name");
+    world.register_serde_versioned::<Position>("Position", 2);
+    world.register_migration("Position", 0, |mut data| {
+        data["y"] = 1.0.into();
+        Ok(data)
+    });
+
+    let report = world.load(&bytes[..]).unwrap();
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].entity, Some(1));
+    assert_eq!(report.failed[0].component, "Position");
+    assert_eq!(report.failed[0].version, 0);
+    assert!(!report.ids.contains_key(&1));
+    assert!(report.ids.contains_key(&2));
+}
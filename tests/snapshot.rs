@@ -0,0 +1,106 @@
+extern crate tinyecs;
+
+use std::rc::Rc;
+use std::cell::Cell;
+use tinyecs::*;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Position(i32);
+impl Component for Position {}
+
+#[derive(Clone)]
+pub struct Velocity(i32);
+impl Component for Velocity {}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Frame(i32);
+
+pub struct MoveSystem {
+    added   : Rc<Cell<i32>>,
+    removed : Rc<Cell<i32>>
+}
+impl System for MoveSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Position, Velocity)
+    }
+    fn on_added(&mut self, _ : &mut Entity) {
+        self.added.set(self.added.get() + 1);
+    }
+    fn on_removed(&self, _ : &mut Entity) {
+        self.removed.set(self.removed.get() + 1);
+    }
+    fn process_w(&mut self, entity : &mut Entity, world : &mut WorldHandle) {
+        let mut pos = entity.get_component::<Position>();
+        pos.0 += entity.get_component::<Velocity>().0;
+        if pos.0 >= 3 {
+            entity.remove_component::<Velocity>();
+            entity.refresh();
+        }
+        world.resources.get::<Frame>().0 += 1;
+    }
+}
+
+fn position(world : &mut World, id : i32) -> i32 {
+    world.entity_manager().try_get_entity(id).unwrap().get_component::<Position>().0
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let added = Rc::new(Cell::new(0));
+    let removed = Rc::new(Cell::new(0));
+
+    let mut world = World::new();
+    world.register_clone::<Position>();
+    world.register_clone::<Velocity>();
+    world.register_clone::<Frame>();
+    world.resources().insert(Frame(0));
+    world.set_system(MoveSystem { added : added.clone(), removed : removed.clone() });
+
+    let id = {
+        let mut entity_manager = world.entity_manager();
+        let e = entity_manager.create_entity();
+        e.add_component(Position(0));
+        e.add_component(Velocity(1));
+        e.refresh();
+        e.id
+    };
+
+    world.update();
+    world.update();
+    assert_eq!(position(&mut world, id), 2);
+    assert_eq!(added.get(), 1);
+
+    let snapshot = world.snapshot();
+
+    for _ in 0 .. 3 {
+        world.update();
+    }
+    world.entity_manager().create_entity().refresh();
+    assert_eq!(position(&mut world, id), 3);
+    assert_eq!(removed.get(), 1);
+
+    for _ in 0 .. 2 {
+        world.restore(&snapshot);
+        assert_eq!(position(&mut world, id), 2);
+        assert_eq!(*world.resources().get::<Frame>(), Frame(2));
+        assert_eq!(world.entity_manager().create_entity().id, id + 1);
+
+        world.restore(&snapshot);
+        world.update();
+        assert_eq!(position(&mut world, id), 3);
+        world.update();
+        assert_eq!(added.get(), 1);
+        assert_eq!(removed.get(), 2);
+        removed.set(1);
+    }
+}
+
+#[test]
+#[should_panic(expected = "World has resource Frame, borrowed while making snapshot")]
+fn test_snapshot_borrowed() {
+    let mut world = World::new();
+    world.register_clone::<Frame>();
+    world.resources().insert(Frame(0));
+    let _frame = world.resources().get::<Frame>();
+    world.snapshot();
+}
@@ -0,0 +1,114 @@
+extern crate tinyecs;
+#[cfg(feature = "serde")]
+#[macro_use] extern crate serde;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use tinyecs::*;
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameState {
+    Menu,
+    Game,
+    Pause
+}
+
+pub struct Body;
+impl Component for Body {}
+
+type Log = Rc<RefCell<Vec<&'static str>>>;
+
+/// Logs its name each time it is updated, with or without entities.
+pub struct LogSystem {
+    name : &'static str,
+    log  : Log
+}
+impl System for LogSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Body)
+    }
+    fn process_all(&mut self, _ : &mut Vec<&mut Entity>, _ : &mut WorldHandle, _ : &mut DataList) {
+        self.log.borrow_mut().push(self.name);
+    }
+}
+
+fn take(log : &Log) -> Vec<&'static str> {
+    ::std::mem::take(&mut *log.borrow_mut())
+}
+
+#[test]
+fn test_state_systems() {
+    let log = Rc::new(RefCell::new(vec![]));
+    let system = |name| LogSystem { name, log : log.clone() };
+
+    let mut world = World::new();
+    world.add_state(GameState::Menu);
+    world.set_state_system(StateHook::Update(GameState::Menu), system("menu"));
+    world.set_state_system(StateHook::Enter(GameState::Game), system("enter game"));
+    world.set_state_system(StateHook::Exit(GameState::Game), system("exit game"));
+    world.set_state_system(StateHook::Update(GameState::Game), system("game"));
+    world.set_state_system(StateHook::Enter(GameState::Pause), system("enter pause"));
+    world.set_state_system(StateHook::Exit(GameState::Pause), system("exit pause"));
+    world.set_state_system(StateHook::Update(GameState::Pause), system("pause"));
+    world.entity_manager().create_entity().add_component(Body);
+
+    world.update_with_delta(0.1);
+    assert_eq!(take(&log), vec!["menu"]);
+
+    world.resources().get::<State<GameState>>().set(GameState::Game);
+    assert_eq!(*world.resources().get::<State<GameState>>().current(), GameState::Menu);
+    world.update_with_delta(0.1);
+    assert_eq!(take(&log), vec!["enter game", "game"]);
+
+    world.resources().get::<State<GameState>>().push(GameState::Pause);
+    world.update_with_delta(0.1);
+    world.update_with_delta(0.1);
+    assert_eq!(take(&log), vec!["enter pause", "pause", "pause"]);
+    assert_eq!(world.resources().get::<State<GameState>>().stack(), &[GameState::Game, GameState::Pause]);
+
+    world.resources().get::<State<GameState>>().pop();
+    world.update_with_delta(0.1);
+    assert_eq!(take(&log), vec!["exit pause", "game"]);
+
+    world.resources().get::<State<GameState>>().push(GameState::Pause);
+    world.resources().get::<State<GameState>>().set(GameState::Menu);
+    world.update_with_delta(0.1);
+    assert_eq!(take(&log), vec!["enter pause", "exit pause", "exit game", "menu"]);
+}
+
+#[test]
+#[should_panic(expected = "is already added")]
+fn test_add_state_twice() {
+    let mut world = World::new();
+    world.add_state(GameState::Menu);
+    world.add_state(GameState::Game);
+}
+
+#[test]
+fn test_state_system_without_state() {
+    let mut world = World::new();
+    let log = Rc::new(RefCell::new(vec![]));
+    let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+        world.set_state_system(StateHook::Enter(GameState::Game), LogSystem { name : "enter", log : log.clone() });
+    }));
+    assert!(result.is_err());
+    // no half-registered system is left
+    assert!(world.system_stats().is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_save_state() {
+    let mut world = World::new();
+    world.add_state_serde("GameState", GameState::Menu);
+    world.resources().get::<State<GameState>>().push(GameState::Pause);
+    world.update_with_delta(0.0);
+    let mut bytes = vec![];
+    world.save(&mut bytes, Format::Json).unwrap();
+
+    let mut loaded = World::new();
+    loaded.add_state_serde("GameState", GameState::Game);
+    loaded.load(&bytes[..]).unwrap();
+    assert_eq!(loaded.resources().get::<State<GameState>>().stack(), &[GameState::Menu, GameState::Pause]);
+}
@@ -0,0 +1,124 @@
+extern crate tinyecs;
+#[cfg(feature = "serde")]
+#[macro_use] extern crate serde;
+
+use std::any::TypeId;
+use std::thread;
+use tinyecs::*;
+
+pub struct Loading {
+    progress : u32
+}
+impl Component for Loading {}
+
+pub struct Loaded {
+    progress : u32
+}
+impl Component for Loaded {}
+
+transit_sync_system!(LoadedSystem: Loading => Loaded, |loading| Loaded { progress : loading.progress });
+
+#[test]
+fn test_sync_component() {
+    let mut world = World::new();
+    let (id, lock) = {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.create_entity();
+        entity.add_sync_component(Loading { progress : 0 });
+        entity.write_sync_component::<Loading>().progress = 10;
+        (entity.id, entity.sync_component::<Loading>())
+    };
+
+    thread::spawn(move || {
+        lock.write().unwrap().progress += 90;
+    }).join().unwrap();
+
+    world.set_system(LoadedSystem::new());
+    world.update_with_delta(0.0);
+
+    let mut entity_manager = world.entity_manager();
+    let entity = entity_manager.try_get_entity(id).unwrap();
+    assert_eq!(entity.read_sync_component::<Loading>().progress, 100);
+    assert_eq!(entity.get_component::<Loaded>().progress, 100);
+}
+
+#[test]
+#[should_panic(expected = "use read_sync_component")]
+fn test_sync_component_is_not_boxed() {
+    let entity = Entity::new(0);
+    entity.add_sync_component(Loading { progress : 0 });
+    entity.get_component::<Loading>();
+}
+
+#[derive(Clone, Hash, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Progress(u32);
+impl Component for Progress {
+    const STORAGE : Storage = Storage::Sync;
+
+    fn register(registry : &mut ComponentRegistry) {
+        registry.register(TypeId::of::<Progress>(), "Progress").storage = Storage::Sync;
+        registry.register_clone::<Progress>();
+        registry.register_hash::<Progress>();
+        registry.register_debug::<Progress>();
+        #[cfg(feature = "serde")]
+        registry.register_serde::<Progress>("Progress", 0);
+    }
+}
+
+fn progress_world() -> (World, i32) {
+    let mut world = World::new();
+    world.register_component::<Progress>();
+    let id = {
+        let mut entity_manager = world.entity_manager();
+        let entity = entity_manager.create_entity();
+        entity.add_component(Progress(10));
+        entity.refresh();
+        entity.id
+    };
+    (world, id)
+}
+
+#[test]
+fn test_sync_component_snapshot() {
+    let (mut world, id) = progress_world();
+    let checksum = world.checksum();
+    let snapshot = world.snapshot();
+
+    let lock = world.entity_manager().try_get_entity(id).unwrap().sync_component::<Progress>();
+    lock.write().unwrap().0 = 50;
+    assert!(world.checksum() != checksum);
+    assert!(world.inspect_entity(id).unwrap().components[0].value == Some("Progress(50)".to_string()));
+
+    world.restore(&snapshot);
+    assert_eq!(world.checksum(), checksum);
+    // restored copy does not share the lock, handed to other threads
+    lock.write().unwrap().0 = 70;
+    let mut entity_manager = world.entity_manager();
+    let entity = entity_manager.try_get_entity(id).unwrap();
+    assert_eq!(*entity.read_sync_component::<Progress>(), Progress(10));
+    assert!(entity.try_get_component::<Loaded>().is_none());
+}
+
+#[test]
+fn test_try_get_sync_component() {
+    let entity = Entity::new(0);
+    entity.add_component(Progress(0));
+    assert!(entity.try_get_component::<Progress>().is_none());
+    assert_eq!(entity.read_sync_component::<Progress>().0, 0);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_sync_component_save() {
+    let (world, id) = progress_world();
+    let mut bytes = vec![];
+    world.save(&mut bytes, Format::Json).unwrap();
+
+    let mut loaded = World::new();
+    loaded.register_component::<Progress>();
+    let report = loaded.load(&bytes[..]).unwrap();
+    let mut entity_manager = loaded.entity_manager();
+    let entity = entity_manager.try_get_entity(report.ids[&id]).unwrap();
+    assert_eq!(*entity.read_sync_component::<Progress>(), Progress(10));
+}
@@ -0,0 +1,95 @@
+extern crate tinyecs;
+
+use std::rc::Rc;
+use std::cell::Cell;
+use tinyecs::*;
+
+pub struct Body;
+impl Component for Body {}
+
+pub struct Paused(bool);
+
+pub struct CountSystem {
+    processed : Rc<Cell<i32>>,
+    removed   : Rc<Cell<i32>>
+}
+impl System for CountSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Body)
+    }
+    fn on_removed(&self, _ : &mut Entity) {
+        self.removed.set(self.removed.get() + 1);
+    }
+    fn process_one(&mut self, _ : &mut Entity) {
+        self.processed.set(self.processed.get() + 1);
+    }
+}
+
+fn make_world() -> (World, SystemHandle, Rc<Cell<i32>>, Rc<Cell<i32>>) {
+    let processed = Rc::new(Cell::new(0));
+    let removed = Rc::new(Cell::new(0));
+
+    let mut world = World::new();
+    let handle = world.set_system(CountSystem { processed : processed.clone(), removed : removed.clone() });
+    {
+        let mut entity_manager = world.entity_manager();
+        for _ in 0 .. 2 {
+            entity_manager.create_entity().add_component(Body);
+        }
+    }
+    (world, handle, processed, removed)
+}
+
+#[test]
+fn test_enable_disable() {
+    let (mut world, handle, processed, removed) = make_world();
+
+    world.update();
+    assert_eq!(processed.get(), 2);
+
+    world.set_enabled(handle, false);
+    assert!(!world.is_enabled(handle));
+    world.update();
+    assert_eq!(processed.get(), 2);
+
+    world.set_enabled(handle, true);
+    world.update();
+    assert_eq!(processed.get(), 4);
+    assert_eq!(removed.get(), 0);
+}
+
+#[test]
+fn test_remove_system() {
+    let (mut world, handle, processed, removed) = make_world();
+
+    world.update();
+    assert!(world.remove_system(handle));
+    assert_eq!(removed.get(), 2);
+    assert!(!world.remove_system(handle));
+
+    world.update();
+    assert_eq!(processed.get(), 2);
+}
+
+#[test]
+fn test_run_criteria() {
+    let (mut world, handle, processed, _) = make_world();
+    world.resources().insert(Paused(false));
+    world.set_run_criteria(handle, |resources| !resources.get::<Paused>().0);
+
+    world.update();
+    world.resources().get::<Paused>().0 = true;
+    world.update();
+    world.update();
+    assert_eq!(processed.get(), 2);
+
+    world.set_run_criteria(handle, every_nth_frame(3));
+    for _ in 0 .. 6 {
+        world.update();
+    }
+    assert_eq!(processed.get(), 6);
+
+    world.clear_run_criteria(handle);
+    world.update();
+    assert_eq!(processed.get(), 8);
+}
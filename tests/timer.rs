@@ -0,0 +1,68 @@
+extern crate tinyecs;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use tinyecs::*;
+
+pub struct Body;
+impl Component for Body {}
+
+pub struct RecordSystem {
+    log : Rc<RefCell<Vec<f32>>>
+}
+impl System for RecordSystem {
+    fn aspect(&self) -> Aspect {
+        aspect_all!(Body)
+    }
+    fn process_w(&mut self, _ : &mut Entity, world : &mut WorldHandle) {
+        self.log.borrow_mut().push(world.delta);
+    }
+}
+
+#[test]
+fn test_interval_systems() {
+    let seconds = Rc::new(RefCell::new(vec![]));
+    let frames = Rc::new(RefCell::new(vec![]));
+
+    let mut world = World::new();
+    world.set_interval_system(Interval::Seconds(1.0), RecordSystem { log : seconds.clone() });
+    world.set_interval_system(Interval::Frames(3), RecordSystem { log : frames.clone() });
+    world.entity_manager().create_entity().add_component(Body);
+
+    for _ in 0 .. 7 {
+        world.update_with_delta(0.25);
+    }
+    assert_eq!(*seconds.borrow(), vec![1.0]);
+    assert_eq!(*frames.borrow(), vec![0.75, 0.75]);
+}
+
+#[test]
+fn test_timers() {
+    let mut world = World::new();
+    world.set_system(TimerSystem);
+    let (once, repeating) = {
+        let mut entity_manager = world.entity_manager();
+        let once = entity_manager.create_entity();
+        once.add_component(Timer::once(1.0));
+        let once = once.id;
+        let repeating = entity_manager.create_entity();
+        repeating.add_component(Timer::repeating(0.5));
+        (once, repeating.id)
+    };
+
+    world.update_with_delta(0.75);
+    world.update_with_delta(0.75);
+    world.update_with_delta(0.0);
+
+    let mut entity_manager = world.entity_manager();
+    {
+        let once = entity_manager.try_get_entity(once).unwrap();
+        assert_eq!(once.get_component::<TimerFinished>().times, 1);
+        assert!(!once.has_component::<Timer>());
+    }
+    {
+        let repeating = entity_manager.try_get_entity(repeating).unwrap();
+        assert_eq!(repeating.get_component::<TimerFinished>().times, 3);
+        assert_eq!(repeating.get_component::<Timer>().elapsed, 0.0);
+    }
+}
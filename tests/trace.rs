@@ -0,0 +1,59 @@
+extern crate tinyecs;
+
+use std::env;
+use std::fs;
+use std::time::Duration;
+use tinyecs::*;
+
+pub struct Marker;
+impl Component for Marker {}
+
+pub struct MarkerSystem;
+impl System for MarkerSystem {
+    fn aspect(&self) -> Aspect {
+        Aspect::all::<Marker>()
+    }
+    fn process_one(&mut self, _ : &mut Entity) {
+    }
+}
+
+#[test]
+fn test_trace_ring_buffer() {
+    let mut world = World::new();
+    world.entity_manager().create_entity().add_component(Marker);
+    world.set_system(MarkerSystem);
+    world.enable_trace(2);
+    for _ in 0 .. 3 {
+        world.update_with_delta(0.0);
+    }
+
+    let trace = world.trace().unwrap();
+    let frames = trace.frames().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].number, 1);
+    assert_eq!(frames[1].number, 2);
+    assert!(frames[1].spans.iter().any(|span| span.name == "refresh" && span.category == "world"));
+    assert!(frames[1].spans.iter().any(|span| span.name.ends_with("MarkerSystem") && span.category == "process"));
+
+    let mut json = vec![];
+    trace.write_chrome_trace(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.starts_with("{\"traceEvents\":[{\"name\":\"frame 1\""));
+    assert!(json.contains("\"cat\":\"end_frame\",\"ph\":\"X\""));
+}
+
+#[test]
+fn test_trace_spike_dump() {
+    let path = env::temp_dir().join("tinyecs_trace_spike.json");
+    let _ = fs::remove_file(&path);
+
+    let mut world = World::new();
+    world.enable_trace(10);
+    world.trace_mut().unwrap().dump_on_spike(Duration::new(0, 0), &path);
+    world.update_with_delta(0.0);
+
+    assert!(world.trace_mut().unwrap().take_dump_error().is_none());
+    let json = fs::read_to_string(&path).unwrap();
+    assert!(json.contains("\"name\":\"frame 0\""));
+    fs::remove_file(&path).unwrap();
+}
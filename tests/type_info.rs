@@ -0,0 +1,76 @@
+extern crate tinyecs;
+
+use std::any::{Any, TypeId};
+use tinyecs::*;
+
+pub struct Position {
+    pub x : f32,
+    pub y : f32
+}
+impl Component for Position {}
+
+impl Reflect for Position {
+    fn fields() -> Vec<FieldInfo> {
+        vec![FieldInfo { name : "x", type_name : "f32" }, FieldInfo { name : "y", type_name : "f32" }]
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub struct Velocity;
+impl Component for Velocity {}
+
+#[test]
+fn test_type_info() {
+    let entity = Entity::new(0);
+    entity.add_component(Position { x : 1.0, y : 2.0 });
+
+    let info = type_info(TypeId::of::<Position>()).unwrap();
+    assert!(info.name.ends_with("::Position"));
+    assert_eq!(info.short_name(), "Position");
+    assert_eq!(info.size, 8);
+    assert_eq!(info.align, 4);
+    assert!(info.fields.is_none());
+
+    let mut world = World::new();
+    world.register_reflect::<Position>();
+    let fields = type_info(TypeId::of::<Position>()).unwrap().fields.unwrap();
+    assert_eq!(fields[1].name, "y");
+    let position = entity.get_component::<Position>();
+    assert_eq!(position.x + position.y, 3.0);
+
+    let aspect = Aspect::all::<Position>().except::<Velocity>();
+    assert_eq!(format!("{:?}", aspect), "Aspect { all: [\"Position\"], except: [\"Velocity\"], any_of: [] }");
+}
+
+#[test]
+#[should_panic(expected = "Entity 0 has no component Velocity")]
+fn test_missing_component_name() {
+    let entity = Entity::new(0);
+    entity.add_component(Position { x : 1.0, y : 2.0 });
+    entity.get_component::<Velocity>();
+}
+
+mod a {
+    pub struct Health;
+    impl ::tinyecs::Component for Health {}
+}
+mod b {
+    pub struct Health;
+    impl ::tinyecs::Component for Health {}
+}
+
+#[test]
+fn test_ambiguous_short_name() {
+    let entity = Entity::new(0);
+    entity.add_component(a::Health);
+    entity.add_component(b::Health);
+
+    assert_eq!(type_id_by_name("Health"), None);
+    let name = type_info(TypeId::of::<a::Health>()).unwrap().name;
+    assert_eq!(type_id_by_name(name), Some(TypeId::of::<a::Health>()));
+}
@@ -0,0 +1,443 @@
+//! Derive macros for tinyecs, enabled by its "derive" feature.
+//!
+//! ```ignore
+//! #[derive(Component, Clone, Serialize, Deserialize)]
+//! #[component(name = "Position", clone, serde, version = 1)]
+//! struct Position { x : f32, y : f32 }
+//!
+//! #[system]
+//! fn move_system(pos : &mut Position, vel : &Velocity) {
+//!     pos.x += vel.x;
+//! }
+//!
+//! world.register_component::<Position>();
+//! world.set_system(MoveSystem);
+//! ```
+
+extern crate proc_macro;
+extern crate proc_macro2;
+extern crate syn;
+#[macro_use] extern crate quote;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use syn::{parse_macro_input, DeriveInput, Data, Fields, Index, ItemFn, FnArg, Pat, Type, PathArguments, GenericArgument,
+          Ident, LitStr, LitInt, Error};
+
+/// Implements `tinyecs::Component`.
+///
+/// Optional `#[component(...)]` attribute:
+///  - `name = "Position"` - name in the registry and in save files, type name by default
+///  - `clone` - register for world snapshots, type must be `Clone`
+///  - `debug` - register for `World::inspect`, type must be `Debug`
+///  - `default` - register for spawning by name, type must be `Default`
+///  - `reflect` - register for `Entity::reflect_component` and console, type must be `Reflect`
+///  - `serde` - register for saving and loading, type must be `Serialize + Deserialize`
+///  - `version = 1` - schema version for saving, 0 by default
+///  - `replicated` - send to clients with `ReplicationServer`, type must be `Serialize + Deserialize`
+///  - `storage = "boxed"` or `"sync"` - how entity keeps the component, see `tinyecs::Storage`
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input : TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match component(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into()
+    }
+}
+
+fn component(input : DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let ident = &input.ident;
+    let mut name = ident.to_string();
+    let mut clone = false;
+    let mut serde = false;
+    let mut debug = false;
+    let mut reflect = false;
+    let mut default = false;
+    let mut replicated = false;
+    let mut version = 0u32;
+    let mut storage = quote!(::tinyecs::Storage::Boxed);
+    let mut sync = false;
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("component")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("clone") {
+                clone = true;
+            } else if meta.path.is_ident("replicated") {
+                replicated = true;
+            } else if meta.path.is_ident("default") {
+                default = true;
+            } else if meta.path.is_ident("reflect") {
+                reflect = true;
+            } else if meta.path.is_ident("debug") {
+                debug = true;
+            } else if meta.path.is_ident("serde") {
+                serde = true;
+            } else if meta.path.is_ident("version") {
+                version = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            } else if meta.path.is_ident("storage") {
+                let value = meta.value()?.parse::<LitStr>()?;
+                storage = match &value.value()[..] {
+                    "boxed" => quote!(::tinyecs::Storage::Boxed),
+                    "sync" => {
+                        sync = true;
+                        quote!(::tinyecs::Storage::Sync)
+                    },
+                    _ => return Err(Error::new_spanned(value, "unknown storage, expected \"boxed\" or \"sync\""))
+                };
+            } else {
+                return Err(meta.error("unknown component attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let register_clone = if clone {
+        quote!(registry.register_clone::<Self>();)
+    } else {
+        quote!()
+    };
+    let register_debug = if debug {
+        quote!(registry.register_debug::<Self>();)
+    } else {
+        quote!()
+    };
+    let register_replicated = if replicated {
+        quote!(registry.register_replicated::<Self>(#name);)
+    } else {
+        quote!()
+    };
+    let register_default = if default {
+        quote!(registry.register_default::<Self>();)
+    } else {
+        quote!()
+    };
+    let register_reflect = if reflect {
+        quote!(registry.register_reflect::<Self>();)
+    } else {
+        quote!()
+    };
+    let register_serde = if serde {
+        quote!(registry.register_serde::<Self>(#name, #version);)
+    } else {
+        quote!()
+    };
+
+    // sync components are shared with other threads
+    let assert_sync = if sync {
+        quote! {
+            fn assert_send_sync<T : Send + Sync>() {}
+            assert_send_sync::<Self>();
+        }
+    } else {
+        quote!()
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::tinyecs::Component for #ident #ty_generics #where_clause {
+            const STORAGE : ::tinyecs::Storage = #storage;
+
+            fn register(registry : &mut ::tinyecs::ComponentRegistry) {
+                #assert_sync
+                registry.register(::std::any::TypeId::of::<Self>(), #name).storage = #storage;
+                #register_clone
+                #register_debug
+                #register_reflect
+                #register_default
+                #register_replicated
+                #register_serde
+            }
+        }
+    })
+}
+
+/// Implements `tinyecs::Reflect` for struct, all fields should be `Reflect`
+/// or marked with `#[reflect(skip)]`. Fields of tuple structs are named "0", "1"...
+#[proc_macro_derive(Reflect, attributes(reflect))]
+pub fn derive_reflect(input : TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match reflect(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into()
+    }
+}
+
+fn reflect(input : DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let ident = &input.ident;
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => return Err(Error::new_spanned(ident, "Reflect can be derived only for structs"))
+    };
+
+    let mut names = vec![];
+    let mut members = vec![];
+    let mut types = vec![];
+    for (n, field) in fields.iter().enumerate() {
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("reflect")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown reflect attribute"))
+                }
+            })?;
+        }
+        if skip {
+            continue;
+        }
+        match *fields {
+            Fields::Named(_) => {
+                let name = field.ident.clone().unwrap();
+                names.push(name.to_string());
+                members.push(quote!(#name));
+            },
+            _ => {
+                let index = Index::from(n);
+                names.push(n.to_string());
+                members.push(quote!(#index));
+            }
+        }
+        types.push(field.ty.clone());
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // `dyn ::path` does not parse in 2015 edition, so the traits are imported
+    Ok(quote! {
+        const _ : () = {
+        use ::tinyecs::Reflect as __Reflect;
+        use ::std::any::Any as __Any;
+
+        impl #impl_generics __Reflect for #ident #ty_generics #where_clause {
+            fn fields() -> Vec<::tinyecs::FieldInfo> {
+                vec![#( ::tinyecs::FieldInfo {
+                    name      : #names,
+                    type_name : ::std::any::type_name::<#types>()
+                } ),*]
+            }
+
+            fn field_names(&self) -> Vec<&'static str> {
+                vec![#( #names ),*]
+            }
+
+            fn field(&self, name : &str) -> Option<&dyn __Reflect> {
+                match name {
+                    #( #names => Some(&self.#members), )*
+                    _ => None
+                }
+            }
+
+            fn field_mut(&mut self, name : &str) -> Option<&mut dyn __Reflect> {
+                match name {
+                    #( #names => Some(&mut self.#members), )*
+                    _ => None
+                }
+            }
+
+            fn as_any(&self) -> &dyn __Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn __Any {
+                self
+            }
+        }
+        };
+    })
+}
+
+/// Turns function into a system.
+///
+/// System struct is named after the function in CamelCase, or given as `#[system(Name)]`.
+/// Parameters may be:
+///  - `&Component`, `&mut Component` - entity should have it
+///  - `Option<&Component>`, `Option<&mut Component>` - if entity has it
+///  - `&mut WorldHandle` or `&WorldHandle`
+///  - `#[resource] &Resource`, `#[resource] &mut Resource` - from `World::resources`
+///  - `Query<Q>`, `Single<Q>`, `OptionalSingle<Q>` - other entities with components `Q`,
+///    such as `Query<(&Camera, &mut Position)>`; with wrong number of entities for `Single`
+///    system skips the frame, the error is written to stderr once
+#[proc_macro_attribute]
+pub fn system(attr : TokenStream, item : TokenStream) -> TokenStream {
+    let name = if attr.is_empty() {
+        None
+    } else {
+        Some(parse_macro_input!(attr as Ident))
+    };
+    let function = parse_macro_input!(item as ItemFn);
+    match system_impl(name, function) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into()
+    }
+}
+
+fn camel_case(name : &str) -> String {
+    name.split('_').filter(|part| !part.is_empty()).map(|part| {
+        let mut chars = part.chars();
+        let first = chars.next().unwrap();
+        first.to_uppercase().chain(chars).collect::<String>()
+    }).collect()
+}
+
+fn is_world_handle(ty : &Type) -> bool {
+    match *ty {
+        Type::Path(ref path) => path.path.segments.last().is_some_and(|segment| segment.ident == "WorldHandle"),
+        _ => false
+    }
+}
+
+/// `Wrapper<T>` to `T`, if last path segment is named `wrapper`.
+fn generic_argument<'a>(ty : &'a Type, wrapper : &str) -> Option<&'a Type> {
+    let path = match *ty {
+        Type::Path(ref path) if path.qself.is_none() => &path.path,
+        _ => return None
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let arguments = match segment.arguments {
+        PathArguments::AngleBracketed(ref arguments) => arguments,
+        _ => return None
+    };
+    let mut types = arguments.args.iter().filter_map(|argument| match *argument {
+        GenericArgument::Type(ref ty) => Some(ty),
+        _ => None
+    });
+    match (types.next(), types.next()) {
+        (Some(ty), None) => Some(ty),
+        _ => None
+    }
+}
+
+fn system_impl(name : Option<Ident>, mut function : ItemFn) -> Result<proc_macro2::TokenStream, Error> {
+    let fn_name = function.sig.ident.clone();
+    let vis = function.vis.clone();
+    let name = name.unwrap_or_else(|| Ident::new(&camel_case(&fn_name.to_string()), Span::call_site()));
+
+    let mut types = vec![];
+    let mut fetches = vec![];
+    let mut args = vec![];
+    let mut data_aspects = vec![];
+    let mut uses_world = false;
+    for (n, input) in function.sig.inputs.iter_mut().enumerate() {
+        let pat = match *input {
+            FnArg::Typed(ref mut pat) => pat,
+            FnArg::Receiver(ref receiver) => return Err(Error::new_spanned(receiver, "system can not take self"))
+        };
+        let resource = pat.attrs.iter().any(|attr| attr.path().is_ident("resource"));
+        pat.attrs.retain(|attr| !attr.path().is_ident("resource"));
+
+        let var = Ident::new(&format!("__param{}", n), Span::call_site());
+        let as_arg = |mutable : bool| if mutable { quote!(&mut *#var) } else { quote!(&*#var) };
+
+        if let Some(Type::Reference(reference)) = generic_argument(&pat.ty, "Option") {
+            let component = &reference.elem;
+            fetches.push(quote!(let mut #var = entity.try_get_component::<#component>();));
+            if reference.mutability.is_some() {
+                args.push(quote!(#var.as_mut().map(|c| &mut **c)));
+            } else {
+                args.push(quote!(#var.as_ref().map(|c| &**c)));
+            }
+            continue;
+        }
+
+        let query = ["Query", "Single", "OptionalSingle"].iter()
+            .filter_map(|wrapper| generic_argument(&pat.ty, wrapper).map(|query| (*wrapper, query)))
+            .next();
+        if let Some((wrapper, query)) = query {
+            let n = data_aspects.len();
+            let param = match *pat.pat {
+                Pat::Ident(ref ident) => ident.ident.to_string(),
+                _ => format!("#{}", n)
+            };
+            let fetch = match wrapper {
+                "Query" => quote!(query),
+                "Single" => quote!(single),
+                _ => quote!(optional_single)
+            };
+            let system = name.to_string();
+            data_aspects.push(query.clone());
+            fetches.push(quote! {
+                let #var = match data.#fetch::<#query>(#n) {
+                    Ok(query) => query,
+                    Err(error) => {
+                        ::tinyecs::report_query_error(#system, #param, &error);
+                        return;
+                    }
+                };
+            });
+            args.push(quote!(#var));
+            continue;
+        }
+
+        let reference = match *pat.ty {
+            Type::Reference(ref reference) => reference,
+            ref ty => return Err(Error::new_spanned(ty, "system parameter should be a reference or Option of reference"))
+        };
+        let mutable = reference.mutability.is_some();
+        let ty = &reference.elem;
+
+        if is_world_handle(ty) {
+            uses_world = true;
+            args.push(if mutable { quote!(&mut *world) } else { quote!(&*world) });
+        } else if resource {
+            uses_world = true;
+            fetches.push(quote!(let mut #var = __resources.get::<#ty>();));
+            args.push(as_arg(mutable));
+        } else {
+            types.push(ty.clone());
+            fetches.push(quote!(let mut #var = entity.get_component::<#ty>();));
+            args.push(as_arg(mutable));
+        }
+    }
+
+    let process = match (uses_world, !data_aspects.is_empty()) {
+        (false, false) => quote!(process_one(&mut self, entity : &mut ::tinyecs::Entity)),
+        (true, false) => quote!(process_w(&mut self, entity : &mut ::tinyecs::Entity, world : &mut ::tinyecs::WorldHandle)),
+        (false, true) => quote!(process_d(&mut self, entity : &mut ::tinyecs::Entity, data : &mut ::tinyecs::DataList)),
+        (true, true) => quote!(process_wd(&mut self, entity : &mut ::tinyecs::Entity, world : &mut ::tinyecs::WorldHandle,
+                                          data : &mut ::tinyecs::DataList))
+    };
+    let resources = if uses_world {
+        quote!(let __resources = world.resources;)
+    } else {
+        quote!()
+    };
+
+    Ok(quote! {
+        #function
+
+        #vis struct #name;
+
+        impl #name {
+            pub fn new() -> #name {
+                #name
+            }
+        }
+
+        impl ::tinyecs::System for #name {
+            fn aspect(&self) -> ::tinyecs::Aspect {
+                ::tinyecs::Aspect::new(vec![#( ::tinyecs::named_type_id::<#types>() ),*], Vec::new())
+            }
+
+            fn data_aspects(&self) -> Vec<::tinyecs::Aspect> {
+                vec![#( <#data_aspects as ::tinyecs::QueryData>::aspect() ),*]
+            }
+
+            fn runs_without_data(&self) -> bool {
+                true
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn #process {
+                #resources
+                #( #fetches )*
+                #fn_name(#( #args ),*);
+            }
+        }
+    })
+}